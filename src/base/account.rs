@@ -1,5 +1,6 @@
 use crate::base::account::AccountType::{Student, Teacher};
 use crate::base::schools::{get_school, get_schools, School};
+use crate::base::untis::UntisSession;
 use crate::utils::constants::URL;
 use crate::utils::crypt::{
     decrypt_any, encrypt_any, generate_lanis_key_pair, CryptorError, LanisKeyPair,
@@ -50,6 +51,9 @@ pub struct Account {
     pub key_pair: LanisKeyPair,
     pub client: Client,
     pub cookie_store: Arc<CookieStoreMutex>,
+    /// The logged in Untis session (if any) <br>
+    /// Use [Account::get_untis_session] to create it from [AccountSecrets::untis_secrets]
+    pub untis_session: Option<UntisSession>,
 }
 
 /// The account info
@@ -134,6 +138,7 @@ impl Account {
             key_pair,
             client,
             cookie_store,
+            untis_session: None,
         };
 
         account.create_session().await?;
@@ -427,6 +432,39 @@ impl Account {
         }
     }
 
+    /// Returns the [UntisSession] of this account and logs in if there is none yet <br>
+    /// The session gets recreated if [AccountSecrets::untis_secrets] changed
+    pub async fn get_untis_session(&mut self) -> Result<UntisSession, Error> {
+        let secrets = match &self.secrets.untis_secrets {
+            Some(secrets) => secrets.to_owned(),
+            None => {
+                return Err(Error::Credentials(
+                    "no untis secrets are set for this account".to_string(),
+                ))
+            }
+        };
+
+        self.get_untis_session_for(&secrets).await
+    }
+
+    /// Like [Account::get_untis_session] but for the given secrets (e.g. of [crate::modules::timetable::Provider::Untis]) <br>
+    /// The session is stored in [Account::untis_session] and reused as long as the same secrets are used
+    pub async fn get_untis_session_for(
+        &mut self,
+        secrets: &UntisSecrets,
+    ) -> Result<UntisSession, Error> {
+        if let Some(session) = &self.untis_session {
+            if session.secrets() == secrets {
+                return Ok(session.to_owned());
+            }
+        }
+
+        let session = UntisSession::new(secrets.to_owned()).await?;
+        self.untis_session = Some(session.to_owned());
+
+        Ok(session)
+    }
+
     pub fn is_supported(&self, feature: Feature) -> bool {
         if self.features.contains(&feature) {
             true
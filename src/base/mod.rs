@@ -1,3 +1,3 @@
 pub mod account;
 pub mod schools;
-
+pub mod untis;
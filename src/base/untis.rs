@@ -0,0 +1,343 @@
+use crate::base::account::UntisSecrets;
+use crate::Error;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use untis::jsonrpc::ErrorCode;
+
+/// Untis drops idle sessions after a while, so we log in again before reusing a session that was idle for this long
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// The maximum number of requests one [UntisSession] sends at the same time <br>
+/// The Untis client handles one request at a time, so every parallel request needs its own login
+pub const UNTIS_MAX_PARALLEL_REQUESTS: usize = 4;
+
+/// A reusable session for the Untis API <br>
+/// The session logs in once and is shared between all clones, so it can (and should) be used for many requests. <br>
+/// Parallel requests log in additional clients (up to [UNTIS_MAX_PARALLEL_REQUESTS]) which are kept for later requests. <br>
+/// If the session expires it gets refreshed automatically.
+#[derive(Clone)]
+pub struct UntisSession {
+    inner: Arc<UntisSessionInner>,
+}
+
+struct UntisSessionInner {
+    secrets: UntisSecrets,
+    school: untis::School,
+    /// Logged in clients that aren't used by a request right now
+    clients: Mutex<Vec<PooledClient>>,
+    /// Limits the number of clients (and parallel requests) to [UNTIS_MAX_PARALLEL_REQUESTS]
+    permits: Semaphore,
+    /// Used for API methods that aren't supported by [untis::Client]
+    http_client: Client,
+    /// Results of requests (master data, exams) that don't change during a session, keyed by method and params
+    cache: Mutex<BTreeMap<String, serde_json::Value>>,
+}
+
+struct PooledClient {
+    client: untis::Client,
+    last_used: Instant,
+}
+
+impl UntisSession {
+    /// Looks up the school of the provided [UntisSecrets] and logs in
+    pub async fn new(secrets: UntisSecrets) -> Result<UntisSession, Error> {
+        let school_name = secrets.school_name.to_owned();
+        let school = tokio::task::spawn_blocking(move || {
+            untis::schools::get_by_name(&school_name)
+                .map_err(|e| Error::Credentials(format!("failed to get school: '{}'", e)))
+        })
+        .await
+        .map_err(|e| Error::Threading(format!("Failed to join handle: '{}'", e)))??;
+
+        let session = UntisSession {
+            inner: Arc::new(UntisSessionInner {
+                secrets,
+                school,
+                clients: Mutex::new(Vec::new()),
+                permits: Semaphore::new(UNTIS_MAX_PARALLEL_REQUESTS),
+                http_client: Client::new(),
+                cache: Mutex::new(BTreeMap::new()),
+            }),
+        };
+        session.refresh().await?;
+
+        Ok(session)
+    }
+
+    /// The secrets that were used to create this session
+    pub fn secrets(&self) -> &UntisSecrets {
+        &self.inner.secrets
+    }
+
+    /// The Untis school this session is logged in to
+    pub fn school(&self) -> &untis::School {
+        &self.inner.school
+    }
+
+    /// Returns the current Untis session info (ids of the user and its class)
+    pub async fn info(&self) -> Result<untis::Session, Error> {
+        self.request(|client| Ok(client.session().to_owned())).await
+    }
+
+    /// Returns all classes of the school
+    pub async fn classes(&self) -> Result<Vec<untis::Class>, Error> {
+        self.request(|client| client.classes()).await
+    }
+
+    /// Returns all teachers of the school <br>
+    /// NOTE: Many schools don't allow students to list teachers
+    pub async fn teachers(&self) -> Result<Vec<untis::Teacher>, Error> {
+        self.request(|client| client.teachers()).await
+    }
+
+    /// Returns all rooms of the school
+    pub async fn rooms(&self) -> Result<Vec<untis::Room>, Error> {
+        self.request(|client| client.rooms()).await
+    }
+
+    /// Returns all subjects of the school
+    pub async fn subjects(&self) -> Result<Vec<untis::Subject>, Error> {
+        self.request(|client| client.subjects()).await
+    }
+
+    /// Forces a new login (the old sessions get logged out)
+    pub async fn refresh(&self) -> Result<(), Error> {
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || {
+            // Dropping the old clients logs them out
+            drop(std::mem::take(&mut *lock(&inner.clients)?));
+            let client = inner.login()?;
+            inner.put_back(client)
+        })
+        .await
+        .map_err(|e| Error::Threading(format!("Failed to join handle: '{}'", e)))?
+    }
+
+    /// Runs `f` with a logged in [untis::Client] on a blocking thread <br>
+    /// Up to [UNTIS_MAX_PARALLEL_REQUESTS] requests run at the same time, each with its own client. <br>
+    /// Logs in again if the client was idle for too long or if Untis rejects the session and retries once
+    pub(crate) async fn request<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: Fn(&mut untis::Client) -> Result<T, untis::Error> + Send + 'static,
+    {
+        let _permit =
+            self.inner.permits.acquire().await.map_err(|e| {
+                Error::Threading(format!("failed to wait for the session: '{}'", e))
+            })?;
+
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || {
+            let mut client = inner.take_client()?;
+            let result = match f(&mut client) {
+                Err(untis::Error::Rpc(e)) if e.code == ErrorCode::NotAuthenticated.as_isize() => {
+                    drop(client);
+                    client = inner.login()?;
+                    f(&mut client)
+                }
+                result => result,
+            };
+            inner.put_back(client)?;
+
+            result.map_err(|e| Error::UntisAPI(format!("request failed: '{}'", e)))
+        })
+        .await
+        .map_err(|e| Error::Threading(format!("Failed to join handle: '{}'", e)))?
+    }
+
+    /// Sends a JSON-RPC request directly (for methods that aren't supported by [untis::Client]) <br>
+    /// The inner result contains the error if Untis answered with one (e.g. if the user has no access)
+    pub(crate) async fn rpc<T, P>(
+        &self,
+        method: &'static str,
+        params: P,
+    ) -> Result<Result<T, untis::jsonrpc::Error>, Error>
+    where
+        T: DeserializeOwned,
+        P: Serialize,
+    {
+        #[derive(Serialize)]
+        struct JsonRequest<'a, P> {
+            jsonrpc: &'static str,
+            id: &'static str,
+            method: &'static str,
+            params: &'a P,
+        }
+
+        #[derive(Deserialize)]
+        struct JsonResponse<T> {
+            result: Option<T>,
+            error: Option<untis::jsonrpc::Error>,
+        }
+
+        let url = format!(
+            "https://{}/WebUntis/jsonrpc.do?school={}",
+            self.inner.school.server, self.inner.school.login_name
+        );
+        let request = JsonRequest {
+            jsonrpc: "2.0",
+            id: "lanis-rs",
+            method,
+            params: &params,
+        };
+
+        let mut retried = false;
+        loop {
+            let session_id = self.info().await?.session_id;
+            let response = self
+                .inner
+                .http_client
+                .post(&url)
+                .header("Cookie", format!("JSESSIONID={}", session_id))
+                .json(&request)
+                .send()
+                .await
+                .map_err(|e| Error::Network(format!("failed to send '{}': '{}'", method, e)))?;
+            let text = response
+                .text()
+                .await
+                .map_err(|e| Error::Parsing(format!("failed to parse response as text '{}'", e)))?;
+            let json = serde_json::from_str::<JsonResponse<T>>(&text).map_err(|e| {
+                Error::Parsing(format!("failed to parse response of '{}': '{}'", method, e))
+            })?;
+
+            match (json.result, json.error) {
+                (_, Some(error))
+                    if error.code == ErrorCode::NotAuthenticated.as_isize() && !retried =>
+                {
+                    retried = true;
+                    self.refresh().await?;
+                }
+                (_, Some(error)) => return Ok(Err(error)),
+                (Some(result), None) => return Ok(Ok(result)),
+                (None, None) => {
+                    return Err(Error::UntisAPI(format!(
+                        "response of '{}' has neither a result nor an error",
+                        method
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Like [UntisSession::rpc] but without params <br>
+    /// Successful results are cached for the lifetime of the session
+    pub(crate) async fn rpc_cached<T>(
+        &self,
+        method: &'static str,
+    ) -> Result<Result<T, untis::jsonrpc::Error>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        self.rpc_cached_with(method, ()).await
+    }
+
+    /// Like [UntisSession::rpc] but successful results are cached per method and params for the lifetime of the session
+    pub(crate) async fn rpc_cached_with<T, P>(
+        &self,
+        method: &'static str,
+        params: P,
+    ) -> Result<Result<T, untis::jsonrpc::Error>, Error>
+    where
+        T: DeserializeOwned,
+        P: Serialize,
+    {
+        let key = format!(
+            "{}:{}",
+            method,
+            serde_json::to_string(&params).unwrap_or_default()
+        );
+        let cached = lock(&self.inner.cache)?.get(&key).cloned();
+        let value = match cached {
+            Some(value) => value,
+            None => match self.rpc::<serde_json::Value, _>(method, params).await? {
+                Ok(value) => {
+                    let mut cache = lock(&self.inner.cache)?;
+                    cache.insert(key, value.to_owned());
+                    value
+                }
+                Err(e) => return Ok(Err(e)),
+            },
+        };
+
+        let result = serde_json::from_value(value).map_err(|e| {
+            Error::Parsing(format!("failed to parse response of '{}': '{}'", method, e))
+        })?;
+        Ok(Ok(result))
+    }
+}
+
+/// Locks `mutex` and fails instead of panicking if a request panicked while holding it
+fn lock<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>, Error> {
+    mutex
+        .lock()
+        .map_err(|e| Error::UntisAPI(format!("the session is unusable: '{}'", e)))
+}
+
+/// Converts an error returned by the Untis API into an [Error]
+pub(crate) fn rpc_error(method: &str, error: untis::jsonrpc::Error) -> Error {
+    Error::UntisAPI(format!(
+        "'{}' failed with code {}: '{}'",
+        method, error.code, error.message
+    ))
+}
+
+impl UntisSessionInner {
+    /// Must only be called from a blocking context
+    fn login(&self) -> Result<untis::Client, Error> {
+        self.school
+            .client_login(&self.secrets.username, &self.secrets.password)
+            .map_err(|e| Error::Credentials(format!("failed to login: '{}'", e)))
+    }
+
+    /// Takes an idle client or logs in a new one if there is none <br>
+    /// Must only be called from a blocking context
+    fn take_client(&self) -> Result<untis::Client, Error> {
+        loop {
+            let pooled = lock(&self.clients)?.pop();
+            match pooled {
+                Some(pooled) if pooled.last_used.elapsed() <= SESSION_IDLE_TIMEOUT => {
+                    return Ok(pooled.client)
+                }
+                // Dropping the expired client logs it out
+                Some(_) => continue,
+                None => return self.login(),
+            }
+        }
+    }
+
+    fn put_back(&self, client: untis::Client) -> Result<(), Error> {
+        lock(&self.clients)?.push(PooledClient {
+            client,
+            last_used: Instant::now(),
+        });
+        Ok(())
+    }
+}
+
+impl Drop for UntisSessionInner {
+    fn drop(&mut self) {
+        // The untis client is blocking and logs out when dropped which panics inside an async context
+        if let Ok(clients) = self.clients.get_mut() {
+            let clients = std::mem::take(clients);
+            if !clients.is_empty() {
+                std::thread::spawn(move || drop(clients));
+            }
+        }
+    }
+}
+
+impl Debug for UntisSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UntisSession")
+            .field("school", &self.inner.school.login_name)
+            .field("username", &self.inner.secrets.username)
+            .finish()
+    }
+}
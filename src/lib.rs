@@ -80,6 +80,16 @@ pub enum LessonUploadError {
     DeletionFailed,
     Unknown,
     UnknownServerError,
+    /// Happens if more than one file is uploaded but [modules::lessons::LessonUploadInfo::multiple_files] is false
+    TooManyFiles,
+    /// The extension of the file (name in the [String]) isn't allowed
+    FileTypeNotAllowed(String),
+    /// The file (name in the [String]) is larger than allowed
+    FileTooLarge(String),
+    /// The upload was cancelled with a [modules::lessons::CancellationToken]
+    Cancelled,
+    /// The uploaded file doesn't show up in the own files
+    NotVerified,
 }
 
 impl std::fmt::Display for LessonUploadError {
@@ -97,6 +107,15 @@ impl std::fmt::Display for LessonUploadError {
             LessonUploadError::UnknownServerError => {
                 write!(f, "LessonUploadError::UnknownServerError")
             }
+            LessonUploadError::TooManyFiles => write!(f, "LessonUploadError::TooManyFiles"),
+            LessonUploadError::FileTypeNotAllowed(e) => {
+                write!(f, "LessonUploadError::FileTypeNotAllowed({e})")
+            }
+            LessonUploadError::FileTooLarge(e) => {
+                write!(f, "LessonUploadError::FileTooLarge({e})")
+            }
+            LessonUploadError::Cancelled => write!(f, "LessonUploadError::Cancelled"),
+            LessonUploadError::NotVerified => write!(f, "LessonUploadError::NotVerified"),
         }
     }
 }
@@ -107,9 +126,9 @@ mod tests {
 
     use crate::base::account::{Account, AccountSecrets, AccountType, UntisSecrets};
     use crate::base::schools::{get_school_id, get_schools, School};
-    use crate::modules::lessons::get_lessons;
+    use crate::modules::lessons::{get_lessons, load_all, Lesson, LoadOptions};
     use crate::modules::timetable;
-    use crate::modules::timetable::{Provider, Week};
+    use crate::modules::timetable::{Provider, UntisElement, UntisType, Week};
 
     use crate::modules::file_storage::FileStoragePage;
     use crate::modules::messages::{
@@ -124,6 +143,42 @@ mod tests {
     use std::{env, fs};
     use stopwatch_rs::StopWatch;
 
+    /// A course without teacher, entries, marks and exams (set the needed fields with `Lesson { .., ..test_lesson(..) }`)
+    fn test_lesson(id: i32, name: &str) -> Lesson {
+        Lesson {
+            id,
+            url: String::new(),
+            name: String::from(name),
+            teacher: String::new(),
+            teacher_short: None,
+            subject_short: None,
+            attendances: std::collections::BTreeMap::new(),
+            entry_latest: None,
+            entries: None,
+            marks: None,
+            exams: None,
+        }
+    }
+
+    /// An entry in the first school hour without content (set the needed fields with `LessonEntry { .., ..test_entry(..) }`)
+    fn test_entry(
+        id: i32,
+        date: chrono::DateTime<chrono::Utc>,
+    ) -> crate::modules::lessons::LessonEntry {
+        crate::modules::lessons::LessonEntry {
+            id,
+            date,
+            school_hours: vec![1],
+            title: String::new(),
+            details: None,
+            homework: None,
+            attachments: None,
+            attachment_number: 0,
+            uploads: None,
+            presence: None,
+        }
+    }
+
     #[tokio::test]
     async fn test_encryption() {
         let text = fs::read_to_string("test_file.txt").unwrap();
@@ -142,6 +197,1265 @@ mod tests {
         assert_eq!(data, decrypted);
     }
 
+    #[test]
+    fn test_datetime_europe_berlin() {
+        use crate::utils::datetime::{date_time_string_to_datetime, datetime_to_local};
+        use chrono::{TimeZone, Timelike, Utc};
+
+        let utc =
+            |date: &str, time: &str| date_time_string_to_datetime(date, time).unwrap().to_utc();
+
+        // Winter time (CET)
+        assert_eq!(
+            utc("15.01.2024", "08:00:00"),
+            Utc.with_ymd_and_hms(2024, 1, 15, 7, 0, 0).unwrap()
+        );
+        // Summer time (CEST)
+        assert_eq!(
+            utc("15.07.2024", "08:00:00"),
+            Utc.with_ymd_and_hms(2024, 7, 15, 6, 0, 0).unwrap()
+        );
+        // Clocks are turned back, 02:30 exists twice
+        assert_eq!(
+            utc("27.10.2024", "02:30:00"),
+            Utc.with_ymd_and_hms(2024, 10, 27, 0, 30, 0).unwrap()
+        );
+        // Clocks are turned forward, 02:30 doesn't exist
+        assert_eq!(
+            utc("31.03.2024", "02:30:00"),
+            Utc.with_ymd_and_hms(2024, 3, 31, 1, 30, 0).unwrap()
+        );
+        assert!(date_time_string_to_datetime("31.02.2024", "08:00:00").is_err());
+
+        let local = datetime_to_local(&Utc.with_ymd_and_hms(2024, 12, 24, 17, 0, 0).unwrap());
+        assert_eq!(local.hour(), 18);
+    }
+
+    #[test]
+    fn test_abbreviations() {
+        use crate::modules::abbreviations::Abbreviations;
+        use crate::modules::lessons::Lesson;
+        use crate::modules::timetable::{LessonEntry, LessonEntryStatus, SchoolHourGrid};
+        use chrono::Utc;
+
+        let lesson = |name: &str, teacher: &str, teacher_short: Option<&str>| Lesson {
+            teacher: String::from(teacher),
+            teacher_short: teacher_short.map(String::from),
+            ..test_lesson(0, name)
+        };
+        let mut lessons = vec![
+            lesson("Mathematik 10a", "Max Mustermann", Some("MUS")),
+            lesson("Physik 10a", "Max Mustermann", Some("MUS")),
+            lesson("Politik und Wirtschaft", "Erika Musterfrau", Some("MFR")),
+            lesson("Deutsch", "Erika Musterfrau", None),
+        ];
+        let entry = |subject: &str, teacher: &str| {
+            LessonEntry::new(
+                LessonEntryStatus::Normal,
+                vec![String::from(subject)],
+                vec![String::from(teacher)],
+                vec![1],
+                Utc::now(),
+                Utc::now(),
+                Vec::new(),
+                None,
+                None,
+            )
+        };
+        let mut week = Week {
+            week: Utc::now().date_naive(),
+            week_type: None,
+            week_type_monday: None,
+            entries: vec![
+                entry("M", "MUS"),
+                entry("Ph", "mus"),
+                entry("PoWi", "MFR"),
+                entry("Ch", "ABC"),
+            ],
+            holidays: Vec::new(),
+            exams: Vec::new(),
+            school_hour_grid: SchoolHourGrid::default(),
+        };
+
+        let abbreviations = Abbreviations::new(&lessons, &[week.clone()]);
+        assert_eq!(abbreviations.teacher("mus").unwrap(), "Max Mustermann");
+        assert_eq!(abbreviations.subject("m").unwrap(), "Mathematik 10a");
+        assert_eq!(abbreviations.subject("PH").unwrap(), "Physik 10a");
+        assert_eq!(
+            abbreviations.subject("PoWi").unwrap(),
+            "Politik und Wirtschaft"
+        );
+        assert!(abbreviations.subject("Ch").is_none());
+
+        abbreviations.resolve_week(&mut week);
+        assert_eq!(week.entries[0].teacher_names, vec!["Max Mustermann"]);
+        assert_eq!(week.entries[0].subject_names, vec!["Mathematik 10a"]);
+        assert_eq!(week.entries[3].teacher_names, vec!["ABC"]);
+
+        abbreviations.resolve_lessons(&mut lessons);
+        assert_eq!(lessons[0].subject_short.as_deref(), Some("M"));
+        assert_eq!(lessons[3].teacher_short.as_deref(), Some("MFR"));
+        assert!(lessons[3].subject_short.is_none());
+
+        // Two courses with the same subject code are resolved by their teacher
+        lessons.push(lesson("Mathematik LK", "Erika Musterfrau", Some("MFR")));
+        week.entries.push(entry("M", "MFR"));
+        let abbreviations = Abbreviations::new(&lessons, &[week.clone()]);
+        assert!(abbreviations.subject("M").is_none());
+        assert_eq!(
+            abbreviations.courses_for("m"),
+            vec!["Mathematik 10a", "Mathematik LK"]
+        );
+        assert_eq!(abbreviations.course("M", "mfr").unwrap(), "Mathematik LK");
+        assert_eq!(abbreviations.subject("PH").unwrap(), "Physik 10a");
+
+        abbreviations.resolve_week(&mut week);
+        assert_eq!(week.entries[0].subject_names, vec!["Mathematik 10a"]);
+        assert_eq!(week.entries[4].subject_names, vec!["Mathematik LK"]);
+
+        abbreviations.resolve_lessons(&mut lessons);
+        assert_eq!(lessons[0].subject_short.as_deref(), Some("M"));
+        assert_eq!(lessons[4].subject_short.as_deref(), Some("M"));
+
+        // The only course of a teacher isn't used for a subject code that doesn't fit it
+        lessons.push(lesson("Mathematik 9b", "Gerd Gruber", Some("GRU")));
+        week.entries.push(entry("Ch", "GRU"));
+        let abbreviations = Abbreviations::new(&lessons, &[week.clone()]);
+        assert!(abbreviations.subject("Ch").is_none());
+        assert!(abbreviations.course("Ch", "GRU").is_none());
+
+        abbreviations.resolve_week(&mut week);
+        assert_eq!(week.entries[5].subject_names, vec!["Ch"]);
+        assert_eq!(week.entries[5].teacher_names, vec!["Gerd Gruber"]);
+    }
+
+    #[test]
+    fn test_timetable_expand_lanis() {
+        use crate::modules::timetable::{LessonEntry, LessonEntryStatus, SchoolHourGrid};
+        use crate::utils::datetime::{datetime_to_local, merge_naive_date_time_to_datetime};
+        use chrono::{Days, NaiveDate, NaiveTime};
+
+        // A plan that is valid from a monday in the summer
+        let plan_monday = NaiveDate::from_ymd_opt(2024, 9, 2).unwrap();
+        let entry = |weekday: u64, week_type| {
+            let date = plan_monday + Days::new(weekday);
+            let time = NaiveTime::from_hms_opt(8, 0, 0).unwrap();
+            let start = merge_naive_date_time_to_datetime(&date, &time)
+                .unwrap()
+                .to_utc();
+            let mut entry = LessonEntry::new(
+                LessonEntryStatus::Normal,
+                vec![String::from("M")],
+                vec![String::from("MUS")],
+                vec![1],
+                start,
+                start + chrono::Duration::minutes(45),
+                Vec::new(),
+                None,
+                None,
+            );
+            entry.week_type = week_type;
+            entry
+        };
+        let plan = Week {
+            week: plan_monday,
+            week_type: Some('A'),
+            // The plan was fetched in a week of type A
+            week_type_monday: Some(NaiveDate::from_ymd_opt(2024, 9, 16).unwrap()),
+            entries: vec![entry(0, None), entry(1, Some('A')), entry(2, Some('B'))],
+            holidays: Vec::new(),
+            exams: Vec::new(),
+            school_hour_grid: SchoolHourGrid::default(),
+        };
+
+        let current_monday = NaiveDate::from_ymd_opt(2024, 9, 16).unwrap();
+
+        let current = plan.expand_lanis(current_monday).unwrap();
+        assert_eq!(current.week, current_monday);
+        assert_eq!(current.week_type, Some('A'));
+        assert_eq!(current.entries.len(), 2);
+        assert_eq!(current.entries[1].week_type, Some('A'));
+
+        let next = plan.expand_lanis(current_monday + Days::new(7)).unwrap();
+        assert_eq!(next.week_type, Some('B'));
+        assert_eq!(next.entries[1].week_type, Some('B'));
+
+        // Weeks before the fetch alternate as well
+        let before = plan.expand_lanis(current_monday - Days::new(21)).unwrap();
+        assert_eq!(before.week_type, Some('B'));
+        assert_eq!(
+            plan.week_type_of(&(current_monday - Days::new(12))),
+            Some('A')
+        );
+
+        // Lessons keep their local time in the winter
+        let winter = plan
+            .expand_lanis(NaiveDate::from_ymd_opt(2025, 1, 6).unwrap())
+            .unwrap();
+        let start = datetime_to_local(&winter.entries[0].start);
+        assert_eq!(
+            start.date_naive(),
+            NaiveDate::from_ymd_opt(2025, 1, 6).unwrap()
+        );
+        assert_eq!(start.time(), NaiveTime::from_hms_opt(8, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_lessons_attendance() {
+        use crate::modules::lessons::{
+            attendance_by_half_year, Attendance, HalfYear, Lesson, LessonEntry, Presence,
+        };
+        use chrono::{NaiveDate, TimeZone, Utc};
+        use std::collections::BTreeMap;
+
+        assert_eq!(Presence::parse("  "), None);
+        assert_eq!(Presence::parse("anwesend"), Some(Presence::Present));
+        assert_eq!(
+            Presence::parse("fehlend\n (entschuldigt)"),
+            Some(Presence::Excused)
+        );
+        assert_eq!(Presence::parse("unentschuldigt"), Some(Presence::Unexcused));
+        assert_eq!(Presence::parse("nicht anwesend"), Some(Presence::Absent));
+        assert_eq!(Presence::parse("verspätet"), Some(Presence::Late));
+
+        let overview = BTreeMap::from([
+            (String::from("fehlstunden"), String::from("7")),
+            (
+                String::from("fehlstunden (unentschuldigt)"),
+                String::from("2"),
+            ),
+            (String::from("verspätungen"), String::from("1")),
+        ]);
+        let attendance = Attendance::from_overview(&overview);
+        assert_eq!(attendance.unexcused, 2);
+        assert_eq!(attendance.absent, 5);
+        assert_eq!(attendance.late, 1);
+        assert_eq!(attendance.missed(), 7);
+
+        let entry = |month: u32, hours: Vec<i32>, presence| LessonEntry {
+            school_hours: hours,
+            presence,
+            ..test_entry(0, Utc.with_ymd_and_hms(2024, month, 10, 0, 0, 0).unwrap())
+        };
+        let lesson = Lesson {
+            attendances: overview,
+            entries: Some(vec![
+                entry(1, vec![1, 2], Some(Presence::Excused)),
+                entry(3, vec![3], Some(Presence::Unexcused)),
+                entry(3, vec![4], Some(Presence::Late)),
+                entry(9, vec![1, 2], Some(Presence::Present)),
+            ]),
+            ..test_lesson(0, "")
+        };
+        assert_eq!(lesson.attendance(), attendance);
+
+        let by_half_year = attendance_by_half_year(&[lesson.clone(), lesson]);
+        let first = HalfYear::of(&NaiveDate::from_ymd_opt(2024, 1, 10).unwrap());
+        let second = HalfYear::of(&NaiveDate::from_ymd_opt(2024, 3, 10).unwrap());
+        assert_eq!(
+            first,
+            HalfYear {
+                school_year: 2023,
+                half: 1
+            }
+        );
+        assert_eq!(by_half_year[&first].excused, 4);
+        assert_eq!(by_half_year[&second].unexcused, 2);
+        assert_eq!(by_half_year[&second].late, 2);
+        assert_eq!(
+            by_half_year[&HalfYear {
+                school_year: 2024,
+                half: 1
+            }]
+                .missed(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_lessons_grades() {
+        use crate::modules::lessons::{
+            average_points, points_to_mark_average, Grade, GradeModifier, GradeScale, LessonMark,
+            MarkWeights,
+        };
+        use chrono::Utc;
+
+        assert_eq!(GradeScale::detect(&["2+", "3", "1"]), GradeScale::Marks);
+        assert_eq!(GradeScale::detect(&["3", "11", "-"]), GradeScale::Points);
+
+        assert_eq!(
+            Grade::parse(" 2+ ", GradeScale::Marks),
+            Some(Grade::Mark(2, GradeModifier::Plus))
+        );
+        assert_eq!(
+            Grade::parse("3", GradeScale::Points),
+            Some(Grade::Points(3))
+        );
+        assert_eq!(
+            Grade::parse("3-", GradeScale::Points),
+            Some(Grade::Mark(3, GradeModifier::Minus))
+        );
+        assert_eq!(Grade::parse("16", GradeScale::Points), None);
+        assert_eq!(Grade::parse("7", GradeScale::Marks), None);
+        assert_eq!(Grade::parse("-", GradeScale::Marks), None);
+
+        for points in 0..=15 {
+            let mark = Grade::Points(points).to_scale(GradeScale::Marks);
+            assert_eq!(mark.points(), points);
+        }
+        assert_eq!(
+            Grade::Points(15).to_scale(GradeScale::Marks).to_string(),
+            "1+"
+        );
+        assert_eq!(
+            Grade::Points(10).to_scale(GradeScale::Marks).to_string(),
+            "2-"
+        );
+        assert_eq!(
+            Grade::Points(0).to_scale(GradeScale::Marks).to_string(),
+            "6"
+        );
+        assert_eq!(
+            Grade::Mark(4, GradeModifier::None).to_scale(GradeScale::Points),
+            Grade::Points(5)
+        );
+
+        let mark = |name: &str, category: &str, grade| LessonMark {
+            name: String::from(name),
+            date: Utc::now(),
+            mark: String::new(),
+            grade,
+            category: Some(String::from(category)),
+            comment: None,
+        };
+        let marks = vec![
+            mark("Klausur 1", "Schriftlich", Some(Grade::Points(12))),
+            mark("Mündlich", "Mündlich", Some(Grade::Points(6))),
+            mark("Referat", "Mündlich", None),
+        ];
+
+        assert_eq!(average_points(&marks, &MarkWeights::new()), Some(9.0));
+        let weights = MarkWeights::from([(String::from("schriftlich"), 2)]);
+        assert_eq!(average_points(&marks, &weights), Some(10.0));
+        assert_eq!(average_points(&[], &weights), None);
+        assert_eq!(points_to_mark_average(11.0), 2.0);
+    }
+
+    #[test]
+    fn test_lessons_exam_schedule() {
+        use crate::modules::lessons::{exam_schedule, ExamSource, Lesson, LessonExam};
+        use chrono::{NaiveDate, NaiveTime, TimeDelta};
+
+        let exam = LessonExam::parse("12.03.2024 08:00 - 09:30 Uhr Klausur 1", false);
+        assert_eq!(exam.day, NaiveDate::from_ymd_opt(2024, 3, 12));
+        assert_eq!(exam.start, NaiveTime::from_hms_opt(8, 0, 0));
+        assert_eq!(exam.duration(), Some(TimeDelta::minutes(90)));
+        assert_eq!(exam.name, "Klausur 1");
+
+        let exam = LessonExam::parse("05.02.2024 10:15 Klausur 2 (135 min)", true);
+        assert_eq!(exam.end, NaiveTime::from_hms_opt(12, 30, 0));
+        assert_eq!(exam.name, "Klausur 2");
+        let json = serde_json::to_string(&exam).unwrap();
+        assert_eq!(serde_json::from_str::<LessonExam>(&json).unwrap(), exam);
+
+        let exam = LessonExam::parse("20.11.2023 Lernkontrolle", true);
+        assert_eq!(exam.date, "20.11.2023");
+        assert_eq!(exam.start, None);
+        assert_eq!(exam.name, "Lernkontrolle");
+        assert_eq!(LessonExam::parse("Nachschreibtermin", false).day, None);
+
+        let lesson = |id: i32, name: &str, exams: Vec<LessonExam>| Lesson {
+            teacher: String::from("Max Mustermann"),
+            exams: Some(exams),
+            ..test_lesson(id, name)
+        };
+        let lessons = vec![
+            lesson(
+                1,
+                "Mathematik",
+                vec![LessonExam::parse("12.03.2024 Klausur", false)],
+            ),
+            lesson(
+                2,
+                "Physik",
+                vec![
+                    LessonExam::parse("05.02.2024 Test", true),
+                    LessonExam::parse("Nachschreibtermin", false),
+                ],
+            ),
+        ];
+
+        let schedule = exam_schedule(&lessons);
+        assert_eq!(schedule.len(), 2);
+        assert_eq!(schedule[0].course.as_deref(), Some("Physik"));
+        assert_eq!(schedule[0].teacher.as_deref(), Some("Max Mustermann"));
+        assert_eq!(schedule[1].source, ExamSource::Lesson(1));
+    }
+
+    #[test]
+    fn test_lessons_course_material_paths() {
+        use crate::modules::lessons::{course_material_paths, Attachment, Lesson, LessonEntry};
+        use chrono::{TimeZone, Utc};
+
+        let attachment = |name: &str| Attachment {
+            name: String::from(name),
+            size: 1024,
+            url: format!(
+                "https://start.schulportal.hessen.de/meinunterricht.php?f={}",
+                name
+            ),
+        };
+        let entry = |id: i32, day: u32, attachments: Vec<Attachment>| LessonEntry {
+            attachment_number: attachments.len() as i32,
+            attachments: Some(attachments),
+            ..test_entry(id, Utc.with_ymd_and_hms(2024, 3, day, 8, 0, 0).unwrap())
+        };
+        let lesson = Lesson {
+            entries: Some(vec![
+                entry(
+                    1,
+                    12,
+                    vec![attachment("AB 1.pdf"), attachment("Lösung?.pdf")],
+                ),
+                entry(2, 14, vec![attachment("AB 2.pdf")]),
+                entry(3, 14, vec![]),
+            ]),
+            ..test_lesson(0, "Mathe 10/a")
+        };
+
+        let paths = course_material_paths(&[lesson], Path::new("mirror"))
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                Path::new("mirror/Mathe 10_a/2024-03-12/AB 1.pdf"),
+                Path::new("mirror/Mathe 10_a/2024-03-12/Lösung_.pdf"),
+                Path::new("mirror/Mathe 10_a/2024-03-14_2/AB 2.pdf"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lessons_upload_validation() {
+        use crate::modules::lessons::{LessonUploadInfo, UploadFile};
+        use crate::LessonUploadError;
+
+        let info = |multiple_files: bool| LessonUploadInfo {
+            course_id: Some(1),
+            entry_id: Some(2),
+            start: None,
+            end: None,
+            multiple_files,
+            unlimited_tries: true,
+            visibility: None,
+            automatic_deletion: None,
+            allowed_file_types: vec![String::from("pdf"), String::from(".JPG")],
+            max_file_size: String::from("1 KB"),
+            extra: None,
+            own_files: vec![],
+            public_files: vec![],
+        };
+        let file = |name: &str, size: usize| UploadFile::from_bytes(name, vec![0; size]);
+
+        assert!(info(false).allows_file_type("PDF"));
+        assert!(!info(false).allows_file_type("exe"));
+        assert_eq!(info(false).max_file_size_bytes().await, Some(1024));
+
+        assert_eq!(
+            info(false).validate(&[file("Foto.jpg", 1024)]).await,
+            Ok(())
+        );
+        assert_eq!(
+            info(false)
+                .validate(&[file("a.pdf", 10), file("b.pdf", 10)])
+                .await,
+            Err(Error::LessonUploadError(LessonUploadError::TooManyFiles))
+        );
+        assert_eq!(
+            info(true).validate(&[file("virus.exe", 10)]).await,
+            Err(Error::LessonUploadError(
+                LessonUploadError::FileTypeNotAllowed(String::from("virus.exe"))
+            ))
+        );
+        assert_eq!(
+            info(true).validate(&[file("scan.pdf", 1025)]).await,
+            Err(Error::LessonUploadError(LessonUploadError::FileTooLarge(
+                String::from("scan.pdf")
+            )))
+        );
+        let files = (0..7)
+            .map(|i| file(&format!("{}.pdf", i), 10))
+            .collect::<Vec<_>>();
+        assert_eq!(info(true).validate(&files).await, Ok(()));
+
+        assert_eq!(file("Scan.PDF", 1).mime_type(), "application/pdf");
+        assert_eq!(file("Foto.jpg", 1).mime_type(), "image/jpeg");
+        assert_eq!(file("notizen", 1).mime_type(), "application/octet-stream");
+    }
+
+    #[tokio::test]
+    async fn test_lessons_upload_cancellation() {
+        use crate::modules::lessons::{
+            CancellationToken, LessonUpload, LessonUploadInfo, UploadFile, UploadOptions,
+        };
+        use crate::LessonUploadError;
+
+        let token = CancellationToken::new();
+        let waiting = tokio::spawn({
+            let token = token.clone();
+            async move { token.cancelled().await }
+        });
+        assert!(!token.is_cancelled());
+        token.cancel();
+        waiting.await.unwrap();
+        assert!(token.is_cancelled());
+
+        let upload = LessonUpload {
+            id: 1,
+            name: String::from("Abgabe"),
+            state: true,
+            url: String::new(),
+            uploaded: None,
+            date: None,
+            info: Some(LessonUploadInfo {
+                course_id: Some(1),
+                entry_id: Some(2),
+                start: None,
+                end: None,
+                multiple_files: true,
+                unlimited_tries: true,
+                visibility: None,
+                automatic_deletion: None,
+                allowed_file_types: vec![],
+                max_file_size: String::new(),
+                extra: None,
+                own_files: vec![],
+                public_files: vec![],
+            }),
+        };
+        let options = UploadOptions {
+            progress: None,
+            cancellation: Some(token),
+        };
+        let result = upload
+            .upload_files_with(
+                vec![UploadFile::from_bytes("a.pdf", vec![0; 10])],
+                &options,
+                &reqwest::Client::new(),
+            )
+            .await;
+        assert_eq!(
+            result,
+            Err(Error::LessonUploadError(LessonUploadError::Cancelled))
+        );
+    }
+
+    #[test]
+    fn test_lessons_submission_state() {
+        use crate::modules::lessons::{
+            open_submissions, upload_deadline, AutomaticDeletion, Lesson, LessonEntry,
+            LessonUpload, SubmissionState,
+        };
+        use chrono::{NaiveDate, TimeDelta, TimeZone, Utc};
+
+        assert_eq!(
+            AutomaticDeletion::parse("am 31.07.2025"),
+            AutomaticDeletion::On(NaiveDate::from_ymd_opt(2025, 7, 31).unwrap())
+        );
+        assert_eq!(
+            AutomaticDeletion::parse(" keine  Löschung "),
+            AutomaticDeletion::Other(String::from("keine Löschung"))
+        );
+
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+        let upload =
+            |id: i32, state: bool, uploaded: Option<&str>, day: Option<u32>| LessonUpload {
+                id,
+                name: format!("Abgabe {}", id),
+                state,
+                url: String::new(),
+                uploaded: uploaded.map(String::from),
+                date: day.map(|day| Utc.with_ymd_and_hms(2024, 3, day, 23, 59, 0).unwrap()),
+                info: None,
+            };
+
+        assert_eq!(
+            upload(1, true, None, Some(12)).submission_state(&now),
+            SubmissionState::Open
+        );
+        assert_eq!(
+            upload(1, true, Some("1"), Some(12)).submission_state(&now),
+            SubmissionState::Submitted
+        );
+        assert_eq!(
+            upload(1, false, None, Some(9)).submission_state(&now),
+            SubmissionState::Overdue
+        );
+        assert_eq!(
+            upload(1, false, None, None).submission_state(&now),
+            SubmissionState::Closed
+        );
+        assert_eq!(
+            upload(1, true, None, Some(11)).time_remaining(&now),
+            Some(TimeDelta::minutes(35 * 60 + 59))
+        );
+
+        // Lanis doesn't show the year of the deadline
+        let december = Utc.with_ymd_and_hms(2024, 12, 16, 1, 0, 0).unwrap();
+        assert_eq!(
+            upload_deadline("10.01.", "23:59:00", &december),
+            Ok(Utc.with_ymd_and_hms(2025, 1, 10, 22, 59, 0).unwrap())
+        );
+        assert_eq!(
+            upload_deadline("20.12.", "23:59:00", &december),
+            Ok(Utc.with_ymd_and_hms(2024, 12, 20, 22, 59, 0).unwrap())
+        );
+        assert_eq!(
+            upload_deadline("16.12.", "08:00:00", &december),
+            Ok(Utc.with_ymd_and_hms(2024, 12, 16, 7, 0, 0).unwrap())
+        );
+
+        let entry = |id: i32, uploads: Vec<LessonUpload>| LessonEntry {
+            title: String::from("Thema"),
+            uploads: Some(uploads),
+            ..test_entry(id, now)
+        };
+        let lesson = Lesson {
+            teacher: String::from("Erika Musterfrau"),
+            entry_latest: Some(entry(2, vec![upload(3, true, None, Some(20))])),
+            entries: Some(vec![
+                entry(
+                    1,
+                    vec![upload(1, true, None, None), upload(2, false, None, Some(1))],
+                ),
+                entry(2, vec![upload(3, true, None, Some(20))]),
+                entry(3, vec![upload(4, true, Some("2"), Some(15))]),
+            ]),
+            ..test_lesson(7, "Deutsch")
+        };
+
+        let submissions = open_submissions(&[lesson], &now);
+        assert_eq!(
+            submissions
+                .iter()
+                .map(|submission| (submission.upload.id, submission.state))
+                .collect::<Vec<_>>(),
+            vec![
+                (4, SubmissionState::Submitted),
+                (3, SubmissionState::Open),
+                (1, SubmissionState::Open),
+            ]
+        );
+        assert_eq!(submissions[0].course, "Deutsch");
+    }
+
+    #[test]
+    fn test_lessons_homework() {
+        use crate::modules::lessons::{homework, Homework, HomeworkFilter, Lesson, LessonEntry};
+        use chrono::{TimeZone, Utc};
+
+        let entry = |id: i32, day: u32, homework: Option<(&str, bool)>| LessonEntry {
+            title: format!("Stunde {}", id),
+            homework: homework.map(|(description, completed)| Homework {
+                description: String::from(description),
+                completed,
+            }),
+            ..test_entry(id, Utc.with_ymd_and_hms(2024, 3, day, 8, 0, 0).unwrap())
+        };
+        let lesson = |id: i32, name: &str, entries: Vec<LessonEntry>| Lesson {
+            entry_latest: entries.last().cloned(),
+            entries: Some(entries),
+            ..test_lesson(id, name)
+        };
+        let lessons = vec![
+            lesson(
+                1,
+                "Mathe",
+                vec![
+                    entry(1, 4, Some(("S. 12 Nr. 3", true))),
+                    entry(2, 11, Some(("S. 14 Nr. 1", false))),
+                ],
+            ),
+            lesson(
+                2,
+                "Englisch",
+                vec![entry(1, 6, None), entry(2, 8, Some(("Vokabeln", false)))],
+            ),
+        ];
+
+        let all = homework(&lessons, HomeworkFilter::All);
+        assert_eq!(
+            all.iter()
+                .map(|item| (item.course.as_str(), item.description.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Mathe", "S. 14 Nr. 1"),
+                ("Englisch", "Vokabeln"),
+                ("Mathe", "S. 12 Nr. 3"),
+            ]
+        );
+        assert_eq!(homework(&lessons, HomeworkFilter::Open).len(), 2);
+        let completed = homework(&lessons, HomeworkFilter::Completed);
+        assert_eq!(completed.len(), 1);
+        assert_eq!((completed[0].lesson_id, completed[0].entry_id), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_outbox() {
+        use crate::modules::lessons::{homework, Homework, HomeworkFilter, Lesson, LessonEntry};
+        use crate::modules::outbox::{Outbox, OutboxConflict, OutboxDecision};
+        use chrono::Utc;
+
+        let lesson = |description: &str, completed: bool| Lesson {
+            entries: Some(vec![LessonEntry {
+                homework: Some(Homework {
+                    description: String::from(description),
+                    completed,
+                }),
+                ..test_entry(2, Utc::now())
+            }]),
+            ..test_lesson(1, "Mathe")
+        };
+
+        let mut lessons = vec![lesson("S. 12", false)];
+        let open = homework(&lessons, HomeworkFilter::Open).remove(0);
+        let mut outbox = Outbox::new();
+        outbox.set_homework(&open, true);
+        outbox.apply_to_lessons(&mut lessons);
+        assert!(homework(&lessons, HomeworkFilter::Open).is_empty());
+
+        // Toggling back restores the server state, so nothing has to be sent
+        let item = homework(&lessons, HomeworkFilter::Completed).remove(0);
+        outbox.set_homework(&item, false);
+        assert!(outbox.is_empty());
+        outbox.set_homework(&open, true);
+        outbox.set_homework(&open, true);
+        assert_eq!(outbox.entries.len(), 1);
+
+        let entry = &outbox.entries[0];
+        assert_eq!(
+            Outbox::decide(entry, &[lesson("S. 12", false)], &[]),
+            OutboxDecision::Send
+        );
+        assert_eq!(
+            Outbox::decide(entry, &[lesson("S. 12", true)], &[]),
+            OutboxDecision::AlreadyApplied
+        );
+        assert_eq!(
+            Outbox::decide(entry, &[lesson("S. 12 und 13", false)], &[]),
+            OutboxDecision::Conflict(OutboxConflict::HomeworkChanged(String::from(
+                "S. 12 und 13"
+            )))
+        );
+        assert_eq!(
+            Outbox::decide(entry, &[], &[]),
+            OutboxDecision::Conflict(OutboxConflict::Missing)
+        );
+
+        let path = env::temp_dir().join(format!("lanis-rs-outbox-{}.json", std::process::id()));
+        outbox.save(&path).await.unwrap();
+        assert_eq!(Outbox::load(&path).await.unwrap(), outbox);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(Outbox::load(&path).await.unwrap(), Outbox::new());
+    }
+
+    #[test]
+    fn test_markup() {
+        use crate::utils::markup::{Block, Inline, LanisMarkupBuilder, RichText};
+
+        let text = RichText::parse(
+            "Bitte **S. 12 --Nr. 3--** bearbeiten\nH_(2)O und x^(2)\n\n- `fn main()`\n- https://example.org.\nEnde -- wirklich",
+        );
+        assert_eq!(
+            text.blocks[0],
+            Block::Paragraph(vec![
+                Inline::Text(String::from("Bitte ")),
+                Inline::Bold(vec![
+                    Inline::Text(String::from("S. 12 ")),
+                    Inline::Italic(vec![Inline::Text(String::from("Nr. 3"))]),
+                ]),
+                Inline::Text(String::from(" bearbeiten")),
+                Inline::LineBreak,
+                Inline::Text(String::from("H")),
+                Inline::Subscript(vec![Inline::Text(String::from("2"))]),
+                Inline::Text(String::from("O und x")),
+                Inline::Superscript(vec![Inline::Text(String::from("2"))]),
+            ])
+        );
+        assert_eq!(text.blocks.len(), 3);
+        assert_eq!(
+            text.to_plain_text(),
+            "Bitte S. 12 Nr. 3 bearbeiten\nH2O und x2\n\n- fn main()\n- https://example.org.\n\nEnde -- wirklich"
+        );
+        assert_eq!(
+            text.to_markdown(),
+            "Bitte **S. 12 *Nr. 3*** bearbeiten  \nH<sub>2</sub>O und x<sup>2</sup>\n\n- `fn main()`\n- [https://example.org](https://example.org).\n\nEnde -- wirklich"
+        );
+        assert_eq!(RichText::parse(&text.to_lanis_markup()), text);
+        assert_eq!(
+            LanisMarkupBuilder::new()
+                .text("# 1. snake_case * [a] a < b")
+                .line_break()
+                .text("- 2 + 2 = 4")
+                .line_break()
+                .text("3. Stunde")
+                .build_rich_text()
+                .to_markdown(),
+            "\\# 1. snake\\_case \\* \\[a\\] a \\< b  \n\\- 2 + 2 = 4  \n3\\. Stunde"
+        );
+        assert_eq!(RichText::parse("**nicht zu").to_plain_text(), "**nicht zu");
+
+        let html = RichText::parse(
+            "Hallo <b>Klasse</b>,<br />\nsiehe <a href=\"https://example.org\">hier</a>:<ul><li>Aufgabe 1</li><li>Aufgabe &amp; 2</li></ul>",
+        );
+        assert_eq!(
+            html.to_html(),
+            "<p>Hallo <b>Klasse</b>,<br>siehe <a href=\"https://example.org\">hier</a>:</p><ul><li>Aufgabe 1</li><li>Aufgabe &amp; 2</li></ul>"
+        );
+        assert_eq!(
+            html.to_plain_text(),
+            "Hallo Klasse,\nsiehe hier (https://example.org):\n\n- Aufgabe 1\n- Aufgabe & 2"
+        );
+
+        let markup = LanisMarkupBuilder::new()
+            .text("Hallo ")
+            .bold("Frau Musterfrau")
+            .text(",")
+            .paragraph()
+            .text("anbei ")
+            .italic("meine")
+            .text(" Abgabe:")
+            .list(false, &["Aufgabe 1", "Aufgabe 2"])
+            .build();
+        assert_eq!(
+            markup,
+            "Hallo **Frau Musterfrau**,\n\nanbei --meine-- Abgabe:\n\n- Aufgabe 1\n- Aufgabe 2"
+        );
+    }
+
+    #[test]
+    fn test_lessons_archive() {
+        use crate::modules::lessons::{parse_archive, ArchivedHalfYear, HalfYear};
+        use scraper::Html;
+
+        let card = |id: i32, name: &str, teacher: &str| {
+            format!(
+                r#"<div class="col-md-4"><h2>{}</h2>
+                <div class="btn-group"><button title="{}"></button></div>
+                <a class="btn btn-primary" href="meinunterricht.php?a=sus_view&id={}">Öffnen</a>
+                <div class="row"><span>nested</span></div></div>"#,
+                name, teacher, id
+            )
+        };
+        let html = format!(
+            r#"<div id="mappen"><div class="row">{}</div></div>
+            <div id="archiv">
+                <h3>2. Halbjahr 2023/24</h3><div class="row">{}{}</div>
+                <h3>Schuljahr 2023/2024 - 1. HJ</h3><div class="row">{}</div>
+                <h3>Leer</h3><div class="row"></div>
+            </div>"#,
+            card(1, "Mathe", "Max Mustermann (MUS)"),
+            card(2, "Deutsch", "Erika Musterfrau (MUF)"),
+            card(3, "Physik", "Kein Kürzel"),
+            card(4, "Chemie", "Max Mustermann (MUS)"),
+        );
+
+        let archive = parse_archive(&Html::parse_document(&html));
+        assert_eq!(archive.len(), 2);
+
+        assert_eq!(archive[0].label, "2. Halbjahr 2023/24");
+        assert_eq!(
+            archive[0].half_year,
+            Some(HalfYear {
+                school_year: 2023,
+                half: 2
+            })
+        );
+        let ids = archive[0].lessons.iter().map(|l| l.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![2, 3]);
+        assert_eq!(archive[0].lessons[0].teacher, "Erika Musterfrau");
+        assert_eq!(archive[0].lessons[0].teacher_short.as_deref(), Some("MUF"));
+        assert_eq!(archive[0].lessons[1].teacher, "Kein Kürzel");
+        assert_eq!(archive[0].lessons[1].teacher_short, None);
+
+        assert_eq!(
+            archive[1].half_year,
+            Some(HalfYear {
+                school_year: 2023,
+                half: 1
+            })
+        );
+        assert_eq!(archive[1].lessons[0].name, "Chemie");
+
+        assert_eq!(ArchivedHalfYear::parse_label("Archiv"), None);
+        assert!(parse_archive(&Html::parse_document("<div id=\"mappen\"></div>")).is_empty());
+    }
+
+    #[test]
+    fn test_teaching() {
+        use crate::modules::teaching::{parse_submissions, submission_paths};
+        use scraper::Html;
+        use std::path::Path;
+
+        let html = r#"<table><tbody><tr><td>Material</td><td>
+            <a href="meinunterricht.php?a=download&f=1">AB.pdf</a></td></tr></tbody></table>
+            <table><thead><tr><th>Name</th><th>Kommentar</th><th>Dateien</th></tr></thead><tbody>
+            <tr><td> Mustermann,
+                Max </td><td><a href="meinunterricht.php?a=download&f=99">nicht die Abgabe</a></td><td>
+                <a href="meinunterricht.php?a=download&f=12" title="Seite 2 fehlt">Aufgabe 1.pdf</a>
+                <a href="meinunterricht.php?a=download&f=13">Bild: Tafel.png</a>
+                <a href="meinunterricht.php?a=download&f=14">Bild_ Tafel.PNG</a></td></tr>
+            <tr><td>Musterfrau, Erika</td><td></td><td>-</td></tr>
+            <tr><td>Mustermann, Max</td><td></td><td>
+                <a href="meinunterricht.php?a=download&f=15">Aufgabe 1.pdf</a></td></tr>
+            </tbody></table>"#;
+        let submissions = parse_submissions(&Html::parse_document(html)).unwrap();
+        assert_eq!(submissions.len(), 3);
+        assert_eq!(submissions[0].files.len(), 3);
+        assert_eq!(submissions[0].student, "Mustermann, Max");
+        assert!(submissions[0].is_submitted());
+        assert!(!submissions[1].is_submitted());
+        assert_eq!(submissions[0].files[0].index, 12);
+        assert_eq!(
+            submissions[0].files[0].comment.as_deref(),
+            Some("Seite 2 fehlt")
+        );
+        assert!(submissions[0].files[1].url.ends_with("f=13"));
+
+        let paths = submission_paths(&submissions, Path::new("/abgaben"));
+        let paths = paths
+            .iter()
+            .map(|(path, _)| path.to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                Path::new("/abgaben/Mustermann, Max/Aufgabe 1.pdf").to_path_buf(),
+                Path::new("/abgaben/Mustermann, Max/Bild_ Tafel.png").to_path_buf(),
+                Path::new("/abgaben/Mustermann, Max/Bild_ Tafel (2).PNG").to_path_buf(),
+                Path::new("/abgaben/Mustermann, Max (2)/Aufgabe 1.pdf").to_path_buf(),
+            ]
+        );
+        assert!(parse_submissions(&Html::parse_document("<table></table>")).is_err());
+    }
+
+    #[test]
+    fn test_timetable_free_rooms() {
+        use crate::modules::timetable::{
+            LessonEntry, LessonEntryStatus, Participant, SchoolHourGrid, TimeSlot,
+        };
+        use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
+
+        let lesson = |status, teacher: &str, hours: Vec<i32>, start: u32, room: &str| {
+            LessonEntry::new(
+                status,
+                vec![String::from("05a M")],
+                vec![String::from(teacher)],
+                hours.clone(),
+                Utc.with_ymd_and_hms(2024, 9, 2, start, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2024, 9, 2, start + hours.len() as u32, 0, 0)
+                    .unwrap(),
+                vec![String::from(room)],
+                None,
+                None,
+            )
+        };
+        let week = Week {
+            week: NaiveDate::from_ymd_opt(2024, 9, 2).unwrap(),
+            week_type: None,
+            week_type_monday: None,
+            entries: vec![
+                lesson(LessonEntryStatus::Normal, "MUE", vec![1, 2], 6, "B209"),
+                lesson(LessonEntryStatus::Cancelled, "ABC", vec![2], 7, "A101"),
+                lesson(LessonEntryStatus::Normal, "XYZ", vec![3], 8, "C001"),
+            ],
+            holidays: Vec::new(),
+            exams: Vec::new(),
+            school_hour_grid: SchoolHourGrid::default(),
+        };
+
+        // Monday a week later, the timetable repeats
+        let monday = NaiveDate::from_ymd_opt(2024, 9, 9).unwrap();
+        let tuesday = NaiveDate::from_ymd_opt(2024, 9, 10).unwrap();
+
+        assert_eq!(week.rooms(), vec!["A101", "B209", "C001"]);
+        assert_eq!(
+            week.free_rooms(&monday, &TimeSlot::SchoolHour(2)),
+            vec!["A101", "C001"]
+        );
+        assert_eq!(
+            week.free_rooms(&tuesday, &TimeSlot::SchoolHour(2)),
+            week.rooms()
+        );
+        // 08:30 - 09:30 CEST
+        let time = TimeSlot::Time(
+            NaiveTime::from_hms_opt(8, 30, 0).unwrap(),
+            NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+        );
+        assert_eq!(week.free_rooms(&monday, &time), vec!["A101", "C001"]);
+
+        let teacher = week.where_is(
+            &Participant::Teacher(String::from("mue")),
+            &monday,
+            &TimeSlot::SchoolHour(1),
+        );
+        assert_eq!(teacher.len(), 1);
+        assert_eq!(teacher[0].rooms, vec!["B209"]);
+        let class = week.where_is(
+            &Participant::Class(String::from("05A")),
+            &monday,
+            &TimeSlot::SchoolHour(3),
+        );
+        assert_eq!(class[0].rooms, vec!["C001"]);
+        assert!(week
+            .where_is(
+                &Participant::Teacher(String::from("ABC")),
+                &monday,
+                &TimeSlot::SchoolHour(2),
+            )
+            .is_empty());
+        let class = week.where_is(
+            &Participant::Class(String::from("5a")),
+            &monday,
+            &TimeSlot::SchoolHour(3),
+        );
+        assert_eq!(class.len(), 1);
+        assert!(week
+            .where_is(
+                &Participant::Class(String::from("15a")),
+                &monday,
+                &TimeSlot::SchoolHour(3),
+            )
+            .is_empty());
+
+        // A room that is only used in A weeks is free in B weeks
+        let mut entry = lesson(LessonEntryStatus::Normal, "DEF", vec![4], 9, "D100");
+        entry.week_type = Some('A');
+        let mut week = week;
+        week.week_type = Some('A');
+        week.week_type_monday = Some(monday);
+        week.entries.push(entry);
+        let next_monday = NaiveDate::from_ymd_opt(2024, 9, 16).unwrap();
+        assert!(!week
+            .free_rooms(&monday, &TimeSlot::SchoolHour(4))
+            .contains(&String::from("D100")));
+        assert!(week
+            .free_rooms(&next_monday, &TimeSlot::SchoolHour(4))
+            .contains(&String::from("D100")));
+    }
+
+    #[test]
+    fn test_timetable_current_lessons() {
+        use crate::modules::timetable::{
+            LessonEntry, LessonEntryStatus, SchoolHour, SchoolHourGrid,
+        };
+        use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
+
+        // All times are UTC, 06:00 UTC is 08:00 CEST
+        let lesson = |status, subject: &str, hour: i32, start: u32, end: u32| {
+            LessonEntry::new(
+                status,
+                vec![String::from(subject)],
+                vec![String::from("MUE")],
+                vec![hour],
+                Utc.with_ymd_and_hms(2024, 9, 2, start, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2024, 9, 2, end, 0, 0).unwrap(),
+                vec![String::from("B209")],
+                None,
+                None,
+            )
+        };
+        let week = Week {
+            week: NaiveDate::from_ymd_opt(2024, 9, 2).unwrap(),
+            week_type: None,
+            week_type_monday: None,
+            entries: vec![
+                lesson(LessonEntryStatus::Cancelled, "D", 1, 6, 7),
+                lesson(LessonEntryStatus::Normal, "M", 3, 8, 9),
+                lesson(LessonEntryStatus::Normal, "M", 2, 7, 8),
+                lesson(LessonEntryStatus::Abnormal, "E", 4, 9, 10),
+            ],
+            holidays: Vec::new(),
+            exams: Vec::new(),
+            school_hour_grid: SchoolHourGrid::default(),
+        };
+        let at = |hour, minute| Utc.with_ymd_and_hms(2024, 9, 2, hour, minute, 0).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2024, 9, 2).unwrap();
+
+        let day = week.day(&monday);
+        assert_eq!(day.len(), 2);
+        assert_eq!(day[0].school_hours, vec![2, 3]);
+
+        assert!(week.current_lessons(&at(6, 30)).is_empty());
+        assert_eq!(week.next_lessons(&at(6, 30))[0].subjects, vec!["M"]);
+
+        let current = week.current_lessons(&at(8, 30));
+        assert_eq!(current[0].school_hours, vec![2, 3]);
+        assert_eq!(week.next_lessons(&at(7, 30))[0].subjects, vec!["E"]);
+        assert_eq!(week.remaining_lessons(&at(8, 30)).len(), 2);
+        assert!(week.remaining_lessons(&at(10, 0)).is_empty());
+
+        assert_eq!(week.first_lesson(&monday).unwrap().start, at(7, 0));
+        assert_eq!(week.last_lesson(&monday).unwrap().end, at(10, 0));
+        assert!(week
+            .first_lesson(&NaiveDate::from_ymd_opt(2024, 9, 3).unwrap())
+            .is_none());
+
+        // A double lesson with a break in between and a lesson that only takes place in B weeks
+        let grid = SchoolHourGrid::new(vec![
+            SchoolHour {
+                number: 1,
+                start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+                end: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            },
+            SchoolHour {
+                number: 2,
+                start: NaiveTime::from_hms_opt(10, 0, 0).unwrap(),
+                end: NaiveTime::from_hms_opt(11, 0, 0).unwrap(),
+            },
+        ]);
+        let mut b_week = lesson(LessonEntryStatus::Normal, "Ku", 3, 9, 10);
+        b_week.week_type = Some('B');
+        let week = Week {
+            week: monday,
+            week_type: Some('A'),
+            week_type_monday: Some(monday),
+            entries: vec![
+                lesson(LessonEntryStatus::Normal, "M", 1, 6, 7),
+                lesson(LessonEntryStatus::Normal, "M", 2, 8, 9),
+                b_week,
+            ],
+            holidays: Vec::new(),
+            exams: Vec::new(),
+            school_hour_grid: grid,
+        };
+        let day = week.day(&monday);
+        assert_eq!(day.len(), 1);
+        assert_eq!(day[0].school_hours, vec![1, 2]);
+        assert_eq!(week.current_lessons(&at(7, 30))[0].school_hours, vec![1, 2]);
+        assert!(week.next_lessons(&at(7, 30)).is_empty());
+        assert_eq!(
+            week.day(&NaiveDate::from_ymd_opt(2024, 9, 9).unwrap())
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_timetable_to_ics() {
+        use crate::modules::timetable::{
+            to_ics, IcsRecurrence, LessonEntry, LessonEntryStatus, SchoolHourGrid,
+        };
+        use chrono::{NaiveDate, TimeZone, Utc};
+
+        let lesson = |status, day: u32, room: &str| {
+            LessonEntry::new(
+                status,
+                vec![String::from("Mathe")],
+                vec![String::from("MUE")],
+                vec![1],
+                Utc.with_ymd_and_hms(2024, 9, day, 6, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2024, 9, day, 6, 45, 0).unwrap(),
+                vec![String::from(room)],
+                None,
+                Some(String::from("Raum; geändert, bitte beachten")),
+            )
+        };
+        let week = |day: u32, entries| Week {
+            week: NaiveDate::from_ymd_opt(2024, 9, day).unwrap(),
+            week_type: None,
+            week_type_monday: None,
+            entries,
+            holidays: Vec::new(),
+            exams: Vec::new(),
+            school_hour_grid: SchoolHourGrid::default(),
+        };
+        let weeks = vec![
+            week(2, vec![lesson(LessonEntryStatus::Normal, 2, "B209")]),
+            week(9, vec![lesson(LessonEntryStatus::Cancelled, 9, "B209")]),
+            week(16, Vec::new()),
+        ];
+
+        let single = to_ics(&weeks, &IcsRecurrence::None);
+        assert!(single.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(single.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(single.matches("BEGIN:VEVENT").count(), 2);
+        assert!(single.contains("UID:20240902T0800-mathe-mue-b209@lanis-rs\r\n"));
+        assert!(single.contains("DTSTART;TZID=Europe/Berlin:20240902T080000\r\n"));
+        assert!(single.contains("LOCATION:B209\r\n"));
+        assert!(single.contains("DESCRIPTION:MUE\\nRaum\\; geändert\\, bitte beachten\r\n"));
+        assert!(single.contains("STATUS:CANCELLED\r\n"));
+        assert!(single.lines().all(|line| line.len() <= 76));
+
+        let weekly = to_ics(
+            &weeks,
+            &IcsRecurrence::Weekly(NaiveDate::from_ymd_opt(2025, 7, 1)),
+        );
+        assert_eq!(weekly.matches("BEGIN:VEVENT").count(), 2);
+        assert_eq!(
+            weekly
+                .matches("UID:mon-0800-mathe-mue-b209@lanis-rs")
+                .count(),
+            2
+        );
+        assert!(weekly.contains("RRULE:FREQ=WEEKLY;UNTIL=20250701T215959Z\r\n"));
+        assert!(weekly.contains("RECURRENCE-ID;TZID=Europe/Berlin:20240909T080000\r\n"));
+        assert!(weekly.contains("EXDATE;TZID=Europe/Berlin:20240916T080000\r\n"));
+        assert!(!weekly.contains("EXDATE;TZID=Europe/Berlin:20240909T080000"));
+
+        // The same subject in another room at the same time is a different lesson
+        let parallel = vec![week(
+            2,
+            vec![
+                lesson(LessonEntryStatus::Normal, 2, "B209"),
+                lesson(LessonEntryStatus::Normal, 2, "A101"),
+            ],
+        )];
+        let weekly = to_ics(&parallel, &IcsRecurrence::Weekly(None));
+        assert_eq!(weekly.matches("RRULE:FREQ=WEEKLY").count(), 2);
+        assert!(weekly.contains("UID:mon-0800-mathe-mue-a101@lanis-rs\r\n"));
+    }
+
+    #[test]
+    fn test_timetable_school_hour_grid() {
+        use crate::modules::timetable::{SchoolHour, SchoolHourGrid};
+        use chrono::NaiveTime;
+
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        let grid = SchoolHourGrid::new(vec![
+            SchoolHour {
+                number: 3,
+                start: time(9, 50),
+                end: time(10, 35),
+            },
+            SchoolHour {
+                number: 1,
+                start: time(8, 0),
+                end: time(8, 45),
+            },
+            SchoolHour {
+                number: 2,
+                start: time(8, 45),
+                end: time(9, 30),
+            },
+        ]);
+
+        assert_eq!(grid.hours[0].number, 1);
+        assert_eq!(grid.hour(3).unwrap().start, time(9, 50));
+        assert_eq!(grid.hour_at(&time(8, 45)).unwrap().number, 2);
+        assert!(grid.hour_at(&time(9, 40)).is_none());
+        assert!(grid.hour_at(&time(10, 35)).is_none());
+        assert_eq!(grid.next_hour(&time(9, 40)).unwrap().number, 3);
+
+        let breaks = grid.breaks();
+        assert_eq!(breaks.len(), 1);
+        assert_eq!(breaks[0].after, 2);
+        assert_eq!(grid.break_at(&time(9, 30)), Some(breaks[0]));
+        assert!(grid.break_at(&time(9, 50)).is_none());
+    }
+
     #[tokio::test]
     async fn test_schools_get_school_id() {
         let mut schools: Vec<School> = vec![];
@@ -239,7 +1553,7 @@ mod tests {
             let mut stopwatch = StopWatch::start();
             let time_table_week = Week::new(
                 Provider::Lanis(timetable::LanisType::All),
-                &account.client,
+                &mut account,
                 chrono::Local::now().date_naive(),
             )
             .await
@@ -253,7 +1567,7 @@ mod tests {
             let mut stopwatch = StopWatch::start();
             let time_table_week = Week::new(
                 Provider::Lanis(timetable::LanisType::Own),
-                &account.client,
+                &mut account,
                 chrono::Local::now().date_naive(),
             )
             .await
@@ -262,6 +1576,27 @@ mod tests {
             println!("Lanis Own: {:?}", time_table_week);
             println!("Week::new() took {}ms", ms);
             println!();
+
+            // Lanis (Range)
+            let mut stopwatch = StopWatch::start();
+            let weeks = Week::range(
+                Provider::Lanis(timetable::LanisType::Own),
+                &mut account,
+                chrono::Local::now().date_naive(),
+                chrono::Local::now().date_naive() + chrono::Duration::weeks(3),
+                1,
+            )
+            .await
+            .unwrap();
+            let ms = stopwatch.split().split.as_millis();
+            for week in &weeks {
+                println!(
+                    "Lanis Range {} ({:?}): {:?}",
+                    week.week, week.week_type, week
+                );
+            }
+            println!("Week::range() took {}ms", ms);
+            println!();
         } else {
             println!("LanisTimetable is not supported by this account! Skipping.");
         }
@@ -283,8 +1618,11 @@ mod tests {
             account.secrets.untis_secrets = Some(secrets);
 
             let time_table_week = Week::new(
-                Provider::Untis(account.secrets.untis_secrets.as_ref().unwrap().clone()),
-                &account.client,
+                Provider::Untis(
+                    account.secrets.untis_secrets.as_ref().unwrap().clone(),
+                    UntisType::Own,
+                ),
+                &mut account,
                 chrono::Local::now().date_naive() - chrono::Duration::weeks(1),
             )
             .await
@@ -292,16 +1630,117 @@ mod tests {
             let ms = stopwatch.split().split.as_millis();
             println!("Untis: {:?}", time_table_week);
             println!("Week::new() took {}ms", ms);
+
+            // Untis (Session)
+            let session = account.get_untis_session().await.unwrap();
+            for weeks in 0..2 {
+                let mut stopwatch = StopWatch::start();
+                let time_table_week = Week::new_untis(
+                    &session,
+                    UntisType::Own,
+                    chrono::Local::now().date_naive() + chrono::Duration::weeks(weeks),
+                )
+                .await
+                .unwrap();
+                let ms = stopwatch.split().split.as_millis();
+                println!("Untis (Session): {:?}", time_table_week);
+                println!("Week::new_untis() took {}ms", ms);
+            }
+
+            // Untis (Class)
+            let mut stopwatch = StopWatch::start();
+            let time_table_week = Week::new_untis(
+                &session,
+                UntisType::OwnClass,
+                chrono::Local::now().date_naive(),
+            )
+            .await
+            .unwrap();
+            let ms = stopwatch.split().split.as_millis();
+            println!("Untis (Class): {:?}", time_table_week);
+            println!("Week::new_untis() took {}ms", ms);
+
+            // Untis (Range)
+            let mut stopwatch = StopWatch::start();
+            let weeks = Week::range(
+                Provider::Untis(
+                    account.secrets.untis_secrets.as_ref().unwrap().clone(),
+                    UntisType::Own,
+                ),
+                &mut account,
+                chrono::Local::now().date_naive(),
+                chrono::Local::now().date_naive() + chrono::Duration::weeks(5),
+                crate::base::untis::UNTIS_MAX_PARALLEL_REQUESTS,
+            )
+            .await
+            .unwrap();
+            let ms = stopwatch.split().split.as_millis();
+            assert_eq!(weeks.len(), 6);
+            assert!(weeks.windows(2).all(|pair| pair[0].week < pair[1].week));
+            println!("Week::range() (Untis) took {}ms", ms);
+
+            if let Ok(room) = env::var("UNTIS_TEST_ROOM") {
+                let time_table_week = Week::new_untis(
+                    &session,
+                    UntisType::Room(UntisElement::Name(room)),
+                    chrono::Local::now().date_naive(),
+                )
+                .await
+                .unwrap();
+                println!("Untis (Room): {:?}", time_table_week);
+            }
+            println!("Untis classes: {:?}", session.classes().await.unwrap());
         }
 
         println!();
     }
 
+    /// Only reads from Lanis, the upload to test is set with
+    /// `LANIS_TEACHER_COURSE_ID`, `LANIS_TEACHER_ENTRY_ID` and `LANIS_TEACHER_UPLOAD_ID`
+    async fn test_lessons_teacher(account: &Account) {
+        use crate::modules::teaching::{download_submissions, get_submissions};
+
+        let ids = [
+            "LANIS_TEACHER_COURSE_ID",
+            "LANIS_TEACHER_ENTRY_ID",
+            "LANIS_TEACHER_UPLOAD_ID",
+        ]
+        .map(|key| env::var(key).ok().map(|id| id.parse::<i32>().unwrap()));
+        let [Some(course_id), Some(entry_id), Some(upload_id)] = ids else {
+            println!("LANIS_TEACHER_COURSE_ID, LANIS_TEACHER_ENTRY_ID or LANIS_TEACHER_UPLOAD_ID not set! Skipping!");
+            return;
+        };
+
+        let mut stopwatch = StopWatch::start();
+        let submissions = get_submissions(course_id, entry_id, upload_id, account)
+            .await
+            .unwrap();
+        println!(
+            "get_submissions() took {}ms",
+            stopwatch.split().split.as_millis()
+        );
+        println!("\t{:?}", submissions);
+
+        let root = env::temp_dir().join(format!("lanis-rs-submissions-{}", upload_id));
+        let mut stopwatch = StopWatch::start();
+        let result = download_submissions(&submissions, &root, &account.client, |_| {}).await;
+        println!(
+            "download_submissions() took {}ms",
+            stopwatch.split().split.as_millis()
+        );
+        let _ = fs::remove_dir_all(&root);
+        assert!(result.failures.is_empty(), "{:?}", result.failures);
+    }
+
     #[tokio::test]
     async fn test_lessons() {
         let account = create_account().await;
+        if account.account_type == AccountType::Teacher {
+            test_lessons_teacher(&account).await;
+            return;
+        }
         if account.account_type != AccountType::Student {
-            println!("Not a student account! Skipping!");
+            println!("Not a student or teacher account! Skipping!");
             return;
         }
 
@@ -437,6 +1876,28 @@ mod tests {
                 stopwatch.split().split.as_millis()
             );
 
+            let mut stopwatch = StopWatch::start();
+            let loaded = load_all(&account, &LoadOptions::default()).await.unwrap();
+            println!(
+                "load_all() took {}ms ({} lessons, failures: {:?})",
+                stopwatch.split().split.as_millis(),
+                loaded.lessons.len(),
+                loaded.failures
+            );
+
+            if let Some(first) = loaded.lessons.first() {
+                let mut stopwatch = StopWatch::start();
+                let fetched = Lesson::fetch(first.id, &account).await.unwrap();
+                println!(
+                    "Lesson::fetch() took {}ms ('{}' by '{}', {} entries)",
+                    stopwatch.split().split.as_millis(),
+                    fetched.name,
+                    fetched.teacher,
+                    fetched.entries.as_ref().map_or(0, |entries| entries.len())
+                );
+                assert_eq!(fetched.entries, first.entries);
+            }
+
             println!()
         } else {
             println!("Lessons are not supported by this account! Skipping.");
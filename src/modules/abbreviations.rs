@@ -0,0 +1,275 @@
+use crate::base::account::AccountType;
+use crate::base::untis::UntisSession;
+use crate::modules::lessons::Lesson;
+use crate::modules::messages::search_receiver;
+use crate::modules::timetable::Week;
+use crate::Error;
+use reqwest::Client;
+use std::collections::BTreeMap;
+
+/// Resolves teacher Kürzel and subject codes (as used in timetables) to full names <br>
+/// Lookups are case-insensitive
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Abbreviations {
+    /// Kürzel (uppercase) -> full name (e.g. "MUS" -> "Max Mustermann")
+    pub teachers: BTreeMap<String, String>,
+    /// Subject code -> course name (e.g. "M" -> "Mathematik 10a") <br>
+    /// Only contains codes that belong to exactly one course, see [Abbreviations::courses] for the others
+    pub subjects: BTreeMap<String, String>,
+    /// (Subject code, Kürzel) -> course name (e.g. ("M", "MUS") -> "Mathematik 10a") <br>
+    /// Contains every matched course, also if several courses share a subject code
+    pub courses: BTreeMap<(String, String), String>,
+}
+
+impl Abbreviations {
+    /// Cross-references the courses of Mein Unterricht with the given timetable weeks <br>
+    /// A timetable entry belongs to a course if the Kürzel of the teacher matches and the course name fits the subject code (e.g. "M" and "Mathematik").
+    /// If a teacher has more than one fitting course, the one that fits best is used
+    pub fn new(lessons: &[Lesson], weeks: &[Week]) -> Self {
+        let mut abbreviations = Self::default();
+
+        for lesson in lessons {
+            if let Some(teacher_short) = &lesson.teacher_short {
+                if !teacher_short.is_empty() && !lesson.teacher.is_empty() {
+                    abbreviations
+                        .teachers
+                        .insert(teacher_short.to_uppercase(), lesson.teacher.to_owned());
+                }
+            }
+        }
+
+        let mut codes = weeks
+            .iter()
+            .flat_map(|week| week.entries.iter())
+            .flat_map(|entry| {
+                entry.subjects.iter().flat_map(|subject| {
+                    entry
+                        .teachers
+                        .iter()
+                        .map(move |teacher| (subject.to_owned(), teacher.to_owned()))
+                })
+            })
+            .collect::<Vec<_>>();
+        codes.sort();
+        codes.dedup();
+
+        for (code, teacher) in codes {
+            let candidates = lessons
+                .iter()
+                .filter(|lesson| {
+                    lesson
+                        .teacher_short
+                        .as_ref()
+                        .is_some_and(|short| short.eq_ignore_ascii_case(teacher.trim()))
+                })
+                .map(|lesson| (subject_score(&code, &lesson.name), lesson))
+                // A course whose name doesn't fit the code is of another subject (e.g. a course of the teacher in another class)
+                .filter(|(score, _)| *score > 0)
+                .collect::<Vec<_>>();
+            let best = candidates.iter().map(|(score, _)| *score).max();
+            let best = candidates
+                .iter()
+                .filter(|(score, _)| Some(*score) == best)
+                .collect::<Vec<_>>();
+
+            // Ambiguous matches are left out instead of guessing
+            if let [(_, lesson)] = best.as_slice() {
+                abbreviations.courses.insert(
+                    (code.trim().to_owned(), teacher.trim().to_uppercase()),
+                    lesson.name.to_owned(),
+                );
+            }
+        }
+
+        // A subject code only has one course name if all teachers that use it teach the same course
+        let subjects = abbreviations
+            .courses
+            .iter()
+            .filter(|((code, _), name)| {
+                abbreviations
+                    .courses_for(code)
+                    .iter()
+                    .all(|other| other == name)
+            })
+            .map(|((code, _), name)| (code.to_owned(), name.to_owned()))
+            .collect::<Vec<_>>();
+        abbreviations.subjects.extend(subjects);
+
+        abbreviations
+    }
+
+    /// Adds the teacher list of Untis (Kürzel and full names) <br>
+    /// NOTE: Many schools don't allow students to list teachers
+    pub async fn add_untis(&mut self, session: &UntisSession) -> Result<(), Error> {
+        for teacher in session.teachers().await? {
+            let name = format!("{} {}", teacher.first_name, teacher.last_name)
+                .trim()
+                .to_string();
+            if !teacher.name.is_empty() && !name.is_empty() {
+                self.teachers
+                    .entry(teacher.name.to_uppercase())
+                    .or_insert(name);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Looks up all Kürzel of the given weeks that aren't resolved yet in the Lanis teacher search (messages) <br>
+    /// This needs one request per unknown Kürzel
+    pub async fn add_lanis_search(&mut self, weeks: &[Week], client: &Client) -> Result<(), Error> {
+        let mut unknown = weeks
+            .iter()
+            .flat_map(|week| week.entries.iter())
+            .flat_map(|entry| entry.teachers.iter())
+            .map(|teacher| teacher.trim().to_uppercase())
+            .filter(|teacher| !self.teachers.contains_key(teacher))
+            .collect::<Vec<_>>();
+        unknown.sort();
+        unknown.dedup();
+
+        for short in unknown {
+            let receivers = search_receiver(&short, client).await?;
+            let found = receivers
+                .iter()
+                .filter(|receiver| receiver.account_type == AccountType::Teacher)
+                .find_map(|receiver| {
+                    let (name, receiver_short) = split_teacher_name(&receiver.name)?;
+                    receiver_short.eq_ignore_ascii_case(&short).then_some(name)
+                });
+            if let Some(name) = found {
+                self.teachers.insert(short, name);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the full name of the teacher with the given Kürzel
+    pub fn teacher(&self, short: &str) -> Option<&String> {
+        self.teachers.get(&short.trim().to_uppercase())
+    }
+
+    /// Returns the course name of the given subject code <br>
+    /// None if the code is unknown or belongs to more than one course (use [Abbreviations::course] or [Abbreviations::courses_for] then)
+    pub fn subject(&self, code: &str) -> Option<&String> {
+        self.subjects
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(code.trim()))
+            .map(|(_, name)| name)
+    }
+
+    /// Returns the course name of the given subject code taught by the teacher with the given Kürzel
+    pub fn course(&self, code: &str, teacher: &str) -> Option<&String> {
+        self.courses
+            .iter()
+            .find(|((key, short), _)| {
+                key.eq_ignore_ascii_case(code.trim()) && short.eq_ignore_ascii_case(teacher.trim())
+            })
+            .map(|(_, name)| name)
+    }
+
+    /// Returns all course names that use the given subject code (sorted and without duplicates)
+    pub fn courses_for(&self, code: &str) -> Vec<&String> {
+        let mut names = self
+            .courses
+            .iter()
+            .filter(|((key, _), _)| key.eq_ignore_ascii_case(code.trim()))
+            .map(|(_, name)| name)
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Fills [crate::modules::timetable::LessonEntry::teacher_names] and [crate::modules::timetable::LessonEntry::subject_names] <br>
+    /// Subject codes of several courses are resolved with the teachers of the entry. Values that can't be resolved are kept as they are
+    pub fn resolve_week(&self, week: &mut Week) {
+        for entry in week.entries.iter_mut() {
+            entry.teacher_names = entry
+                .teachers
+                .iter()
+                .map(|teacher| self.teacher(teacher).unwrap_or(teacher).to_owned())
+                .collect();
+            entry.subject_names = entry
+                .subjects
+                .iter()
+                .map(|subject| {
+                    entry
+                        .teachers
+                        .iter()
+                        .find_map(|teacher| self.course(subject, teacher))
+                        .or(self.subject(subject))
+                        .unwrap_or(subject)
+                        .to_owned()
+                })
+                .collect();
+        }
+    }
+
+    /// Fills [Lesson::subject_short] and [Lesson::teacher_short] if it is still missing
+    pub fn resolve_lessons(&self, lessons: &mut [Lesson]) {
+        for lesson in lessons.iter_mut() {
+            if lesson
+                .teacher_short
+                .as_ref()
+                .is_none_or(|short| short.is_empty())
+            {
+                lesson.teacher_short = self
+                    .teachers
+                    .iter()
+                    .find(|(_, name)| **name == lesson.teacher)
+                    .map(|(short, _)| short.to_owned());
+            }
+            lesson.subject_short = self
+                .courses
+                .iter()
+                .find(|((_, short), name)| {
+                    **name == lesson.name
+                        && lesson
+                            .teacher_short
+                            .as_ref()
+                            .is_none_or(|teacher| teacher.eq_ignore_ascii_case(short))
+                })
+                .map(|((code, _), _)| code.to_owned());
+        }
+    }
+}
+
+/// Splits "Max Mustermann (MUS)" into the name and the Kürzel
+pub(crate) fn split_teacher_name(text: &str) -> Option<(String, String)> {
+    let text = text.trim();
+    let (name, short) = text.strip_suffix(')')?.rsplit_once(" (")?;
+    let (name, short) = (name.trim(), short.trim());
+    if name.is_empty() || short.is_empty() {
+        return None;
+    }
+
+    Some((name.to_string(), short.to_string()))
+}
+
+/// How well a subject code fits a course name (higher is better)
+fn subject_score(code: &str, name: &str) -> u8 {
+    let code = code.trim().to_lowercase();
+    let name = name.trim().to_lowercase();
+    if code.is_empty() {
+        return 0;
+    }
+
+    if name
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word == code)
+    {
+        3
+    } else if name.starts_with(&code) {
+        2
+    } else {
+        // The letters of the code appear in order in the name (e.g. "PoWi" and "Politik und Wirtschaft")
+        let mut chars = name.chars();
+        if code.chars().all(|c| chars.any(|n| n == c)) {
+            1
+        } else {
+            0
+        }
+    }
+}
@@ -1,10 +1,15 @@
 use crate::base::account::Account;
+use crate::modules::abbreviations::split_teacher_name;
+use crate::modules::calendar::{get_entries, CalendarEntry};
 use crate::utils::constants::URL;
 use crate::utils::conversion::string_to_byte_size;
 use crate::utils::crypt::{decrypt_lanis_encoded_tags, encrypt_lanis_data};
-use crate::utils::datetime::date_time_string_to_datetime;
+use crate::utils::datetime::{date_time_string_to_datetime, datetime_to_local, now_local};
+use crate::utils::download::{sanitize_file_name, DownloadProgress, RemoteFile};
+use crate::utils::markup::RichText;
 use crate::{Error, LessonUploadError};
-use chrono::{DateTime, Datelike, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeDelta, Utc};
+use futures::{stream, StreamExt};
 use markup5ever::interface::tree_builder::TreeSink;
 use regex::Regex;
 use reqwest::header::HeaderMap;
@@ -13,8 +18,12 @@ use reqwest::Client;
 use scraper::{Element, ElementRef, Html, Selector};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
 use std::time::SystemTime;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::Notify;
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct Lesson {
@@ -24,6 +33,9 @@ pub struct Lesson {
     pub teacher: String,
     /// Should always be Some if nothing went wrong
     pub teacher_short: Option<String>,
+    /// The subject code used in the timetable (e.g. "M") <br>
+    /// Only available after [crate::modules::abbreviations::Abbreviations::resolve_lessons]. Several courses can share a code (e.g. two math courses), so it doesn't identify the course on its own
+    pub subject_short: Option<String>,
     pub attendances: BTreeMap<String, String>,
     /// If this is None there is no latest entry
     pub entry_latest: Option<LessonEntry>,
@@ -46,6 +58,194 @@ pub struct LessonEntry {
     pub attachments: Option<Vec<Attachment>>,
     pub attachment_number: i32,
     pub uploads: Option<Vec<LessonUpload>>,
+    /// The attendance of the user in this lesson <br>
+    /// Only available if the history table shows it
+    pub presence: Option<Presence>,
+}
+
+impl LessonEntry {
+    /// The details as [RichText] (see [RichText::parse])
+    pub fn rich_details(&self) -> Option<RichText> {
+        self.details.as_deref().map(RichText::parse)
+    }
+}
+
+/// The attendance marker of a single [LessonEntry]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum Presence {
+    Present,
+    /// Absent and excused
+    Excused,
+    /// Absent and not excused
+    Unexcused,
+    /// Absent but not (yet) known if excused
+    Absent,
+    Late,
+    /// Text that couldn't be assigned
+    Other(String),
+}
+
+impl Presence {
+    /// Parses the text of the attendance column (e.g. "fehlend (entschuldigt)") <br>
+    /// Returns [None] if the text is empty
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let lower = text.to_lowercase();
+        if lower.is_empty() {
+            None
+        } else if lower.contains("verspät") {
+            Some(Presence::Late)
+        } else if lower.contains("unentschuldigt") {
+            Some(Presence::Unexcused)
+        } else if lower.contains("entschuldigt") {
+            Some(Presence::Excused)
+        } else if lower.contains("fehl")
+            || lower.contains("abwesend")
+            || lower.contains("nicht anwesend")
+        {
+            Some(Presence::Absent)
+        } else if lower.contains("anwesend") {
+            Some(Presence::Present)
+        } else {
+            Some(Presence::Other(text))
+        }
+    }
+}
+
+/// Missed school hours and late arrivals
+#[derive(
+    Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Serialize, Deserialize,
+)]
+pub struct Attendance {
+    /// Missed school hours that are excused
+    pub excused: u32,
+    /// Missed school hours that aren't excused
+    pub unexcused: u32,
+    /// Missed school hours where it isn't (yet) known if they are excused
+    pub absent: u32,
+    /// Late arrivals
+    pub late: u32,
+}
+
+impl Attendance {
+    /// All missed school hours
+    pub fn missed(&self) -> u32 {
+        self.excused + self.unexcused + self.absent
+    }
+
+    pub fn add(&mut self, other: &Attendance) {
+        self.excused += other.excused;
+        self.unexcused += other.unexcused;
+        self.absent += other.absent;
+        self.late += other.late;
+    }
+
+    /// Converts the raw overview values of [Lesson::attendances] <br>
+    /// Unknown labels are ignored
+    pub fn from_overview(attendances: &BTreeMap<String, String>) -> Self {
+        let mut attendance = Attendance::default();
+        let mut total = None;
+        for (label, value) in attendances {
+            let value = value
+                .split_whitespace()
+                .next()
+                .and_then(|value| value.parse::<u32>().ok())
+                .unwrap_or_default();
+            let label = label.to_lowercase();
+            if label.contains("versp") {
+                attendance.late += value;
+            } else if label.contains("unentschuldigt") {
+                attendance.unexcused += value;
+            } else if label.contains("entschuldigt") {
+                attendance.excused += value;
+            } else if label.contains("fehl") || label.contains("abwesen") {
+                *total.get_or_insert(0) += value;
+            }
+        }
+
+        if let Some(total) = total {
+            attendance.absent = total.saturating_sub(attendance.excused + attendance.unexcused);
+        }
+
+        attendance
+    }
+
+    /// Counts the [Presence] markers of the given entries (one per school hour)
+    pub fn from_entries(entries: &[LessonEntry]) -> Self {
+        let mut attendance = Attendance::default();
+        for entry in entries {
+            let hours = entry
+                .school_hours
+                .iter()
+                .filter(|hour| **hour > 0)
+                .count()
+                .max(1) as u32;
+            match entry.presence {
+                Some(Presence::Excused) => attendance.excused += hours,
+                Some(Presence::Unexcused) => attendance.unexcused += hours,
+                Some(Presence::Absent) => attendance.absent += hours,
+                Some(Presence::Late) => attendance.late += 1,
+                _ => {}
+            }
+        }
+
+        attendance
+    }
+}
+
+/// A half-year of a school year (the first one starts in August and the second one in February)
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct HalfYear {
+    /// The year in which the school year starts (e.g. 2024 for 2024/25)
+    pub school_year: i32,
+    /// 1 or 2
+    pub half: u8,
+}
+
+impl HalfYear {
+    pub fn of(date: &NaiveDate) -> Self {
+        match date.month() {
+            8..=12 => HalfYear {
+                school_year: date.year(),
+                half: 1,
+            },
+            1 => HalfYear {
+                school_year: date.year() - 1,
+                half: 1,
+            },
+            _ => HalfYear {
+                school_year: date.year() - 1,
+                half: 2,
+            },
+        }
+    }
+}
+
+/// Sums up the attendance of all lessons per half-year <br>
+/// Uses the entries of the lessons, so [Lesson::set_data] (or [load_all]) must be called first
+pub fn attendance_by_half_year(lessons: &[Lesson]) -> BTreeMap<HalfYear, Attendance> {
+    let mut result: BTreeMap<HalfYear, Attendance> = BTreeMap::new();
+    for lesson in lessons {
+        for entry in lesson.entries.iter().flatten() {
+            let half_year = HalfYear::of(&datetime_to_local(&entry.date).date_naive());
+            result
+                .entry(half_year)
+                .or_default()
+                .add(&Attendance::from_entries(std::slice::from_ref(entry)));
+        }
+    }
+
+    result
+}
+
+/// Sums up the overview attendance (see [Lesson::attendance]) of all lessons
+pub fn total_attendance(lessons: &[Lesson]) -> Attendance {
+    let mut total = Attendance::default();
+    for lesson in lessons {
+        total.add(&lesson.attendance());
+    }
+
+    total
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
@@ -55,6 +255,16 @@ pub struct Attachment {
     pub url: String,
 }
 
+impl RemoteFile for Attachment {
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn size(&self) -> Option<u64> {
+        Some(self.size)
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
 pub struct Homework {
     pub description: String,
@@ -73,6 +283,54 @@ pub struct LessonUpload {
     pub info: Option<LessonUploadInfo>,
 }
 
+impl LessonUpload {
+    /// The end of the upload ([LessonUploadInfo::end] or the date shown in the overview)
+    pub fn deadline(&self) -> Option<DateTime<Utc>> {
+        self.info.as_ref().and_then(|info| info.end).or(self.date)
+    }
+
+    /// Returns true if files were submitted (by the badge in the overview or [LessonUploadInfo::own_files])
+    pub fn is_submitted(&self) -> bool {
+        let badge = self.uploaded.as_ref().is_some_and(|uploaded| {
+            let count = uploaded
+                .split_whitespace()
+                .next()
+                .and_then(|count| count.parse::<u32>().ok());
+            !uploaded.is_empty() && count != Some(0)
+        });
+        let own_files = self
+            .info
+            .as_ref()
+            .is_some_and(|info| !info.own_files.is_empty());
+
+        badge || own_files
+    }
+
+    /// The time until [LessonUpload::deadline] (negative if it passed)
+    pub fn time_remaining(&self, now: &DateTime<Utc>) -> Option<TimeDelta> {
+        Some(self.deadline()? - *now)
+    }
+
+    /// The typed state of the upload at `now` <br>
+    /// [SubmissionState::NotYetOpen] is only detected if the info was loaded (see [LessonUpload::get_info])
+    pub fn submission_state(&self, now: &DateTime<Utc>) -> SubmissionState {
+        let start = self.info.as_ref().and_then(|info| info.start);
+        let deadline_passed = self.deadline().is_some_and(|deadline| deadline < *now);
+
+        if start.is_some_and(|start| start > *now) {
+            SubmissionState::NotYetOpen
+        } else if self.is_submitted() {
+            SubmissionState::Submitted
+        } else if deadline_passed {
+            SubmissionState::Overdue
+        } else if self.state {
+            SubmissionState::Open
+        } else {
+            SubmissionState::Closed
+        }
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct LessonUploadInfo {
     pub course_id: Option<i32>,
@@ -83,8 +341,8 @@ pub struct LessonUploadInfo {
     pub multiple_files: bool,
     /// Represents if files can be uploaded unlimited times
     pub unlimited_tries: bool,
-    pub visibility: Option<String>,
-    pub automatic_deletion: Option<String>,
+    pub visibility: Option<UploadVisibility>,
+    pub automatic_deletion: Option<AutomaticDeletion>,
     pub allowed_file_types: Vec<String>,
     pub max_file_size: String,
     /// Has some extra info
@@ -93,6 +351,61 @@ pub struct LessonUploadInfo {
     pub public_files: Vec<LessonUploadInfoPublicFile>,
 }
 
+impl LessonUploadInfo {
+    /// Returns true if the extension is in [LessonUploadInfo::allowed_file_types] <br>
+    /// If Lanis doesn't restrict the file types every extension is allowed
+    pub fn allows_file_type(&self, extension: &str) -> bool {
+        let allowed = self
+            .allowed_file_types
+            .iter()
+            .map(|file_type| {
+                file_type
+                    .trim()
+                    .trim_start_matches('*')
+                    .trim_start_matches('.')
+                    .to_lowercase()
+            })
+            .filter(|file_type| !file_type.is_empty())
+            .collect::<Vec<_>>();
+
+        allowed.is_empty() || allowed.contains(&extension.to_lowercase())
+    }
+
+    /// [LessonUploadInfo::max_file_size] in bytes (None if it can't be parsed)
+    pub async fn max_file_size_bytes(&self) -> Option<u64> {
+        string_to_byte_size(self.max_file_size.to_owned())
+            .await
+            .ok()
+    }
+
+    /// Checks the file types, sizes and number of files before anything is uploaded
+    pub async fn validate(&self, files: &[UploadFile]) -> Result<(), Error> {
+        if files.is_empty() {
+            return Err(Error::InvalidInput("no files to upload".to_string()));
+        }
+        if files.len() > 1 && !self.multiple_files {
+            return Err(Error::LessonUploadError(LessonUploadError::TooManyFiles));
+        }
+
+        let max_file_size = self.max_file_size_bytes().await;
+        for file in files {
+            let extension = file.extension().unwrap_or_default();
+            if !self.allows_file_type(&extension) {
+                return Err(Error::LessonUploadError(
+                    LessonUploadError::FileTypeNotAllowed(file.name.to_owned()),
+                ));
+            }
+            if max_file_size.is_some_and(|max_file_size| file.size() > max_file_size) {
+                return Err(Error::LessonUploadError(LessonUploadError::FileTooLarge(
+                    file.name.to_owned(),
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
 pub struct LessonUploadInfoOwnFile {
     pub name: String,
@@ -100,6 +413,13 @@ pub struct LessonUploadInfoOwnFile {
     pub index: i32,
     pub comment: Option<String>,
 }
+
+impl RemoteFile for LessonUploadInfoOwnFile {
+    fn url(&self) -> &str {
+        &self.url
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
 pub struct LessonUploadInfoPublicFile {
     pub name: String,
@@ -108,6 +428,12 @@ pub struct LessonUploadInfoPublicFile {
     pub person: String,
 }
 
+impl RemoteFile for LessonUploadInfoPublicFile {
+    fn url(&self) -> &str {
+        &self.url
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
 pub struct LessonUploadFileStatus {
     pub name: String,
@@ -115,22 +441,793 @@ pub struct LessonUploadFileStatus {
     pub message: Option<String>,
 }
 
+/// Who can see the files that were uploaded
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum UploadVisibility {
+    /// Other course members can see the uploaded files
+    Public,
+    /// Only teachers can see the uploaded files
+    Private,
+}
+
+/// When uploaded files are deleted by Lanis
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum AutomaticDeletion {
+    /// The files are deleted on this date
+    On(NaiveDate),
+    /// Text that doesn't contain a date
+    Other(String),
+}
+
+/// A date in the label of the automatic deletion (e.g. "31.07.2025")
+static DELETION_DATE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\d{1,2}\.\d{1,2}\.\d{4}").unwrap());
+
+impl AutomaticDeletion {
+    /// Parses the label of the automatic deletion (e.g. "31.07.2025")
+    pub fn parse(text: &str) -> Self {
+        DELETION_DATE_REGEX
+            .find(text)
+            .and_then(|date| NaiveDate::parse_from_str(date.as_str(), "%d.%m.%Y").ok())
+            .map(AutomaticDeletion::On)
+            .unwrap_or_else(|| {
+                AutomaticDeletion::Other(text.split_whitespace().collect::<Vec<_>>().join(" "))
+            })
+    }
+}
+
+/// The state of a [LessonUpload] for the user
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum SubmissionState {
+    /// The upload opens in the future
+    NotYetOpen,
+    /// Files can be uploaded and nothing was submitted yet
+    Open,
+    /// The upload is closed, nothing was submitted and the deadline isn't known
+    Closed,
+    /// At least one file was submitted
+    Submitted,
+    /// The deadline passed without a submission
+    Overdue,
+}
+
+/// The outcome of every step of [LessonUpload::replace]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ReplaceReport {
+    /// The status messages of the upload
+    pub upload: Vec<LessonUploadFileStatus>,
+    /// The file that was replaced
+    pub old_file: LessonUploadInfoOwnFile,
+    /// The new file as listed by Lanis after the upload
+    pub new_file: LessonUploadInfoOwnFile,
+    /// The result of deleting [ReplaceReport::old_file]
+    pub deletion: Result<(), Error>,
+}
+
+/// The maximum number of files Lanis accepts in a single upload request
+pub const MAX_FILES_PER_UPLOAD: usize = 5;
+
+/// Where the content of an [UploadFile] comes from
+pub enum UploadSource {
+    Bytes(Vec<u8>),
+    /// An async reader and the amount of bytes it will return
+    Reader {
+        reader: Box<dyn AsyncRead + Send + Unpin>,
+        size: u64,
+    },
+}
+
+/// A named file that can be uploaded with [LessonUpload::upload_files]
+pub struct UploadFile {
+    /// The file name shown in Lanis (including the extension)
+    pub name: String,
+    pub source: UploadSource,
+}
+
+impl std::fmt::Debug for UploadFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UploadFile")
+            .field("name", &self.name)
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+impl UploadFile {
+    pub fn from_bytes(name: impl Into<String>, bytes: Vec<u8>) -> Self {
+        Self {
+            name: name.into(),
+            source: UploadSource::Bytes(bytes),
+        }
+    }
+
+    /// `size` has to be the exact amount of bytes the reader returns
+    pub fn from_reader(
+        name: impl Into<String>,
+        reader: impl AsyncRead + Send + Unpin + 'static,
+        size: u64,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            source: UploadSource::Reader {
+                reader: Box::new(reader),
+                size,
+            },
+        }
+    }
+
+    /// Opens the file at `path` (it is read while uploading)
+    pub async fn from_path(path: &Path) -> Result<Self, Error> {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or(Error::InvalidInput(format!(
+                "'{}' is not a file",
+                path.display()
+            )))?;
+        let file = tokio::fs::File::open(path).await.map_err(|e| {
+            Error::FileSystem(format!(
+                "failed to open '{}' with error '{}'",
+                path.display(),
+                e
+            ))
+        })?;
+        let size = file
+            .metadata()
+            .await
+            .map_err(|e| {
+                Error::FileSystem(format!(
+                    "failed to read metadata of '{}' with error '{}'",
+                    path.display(),
+                    e
+                ))
+            })?
+            .len();
+
+        Ok(Self::from_reader(name, file, size))
+    }
+
+    pub fn size(&self) -> u64 {
+        match &self.source {
+            UploadSource::Bytes(bytes) => bytes.len() as u64,
+            UploadSource::Reader { size, .. } => *size,
+        }
+    }
+
+    /// The lowercase extension of [UploadFile::name] (without the dot)
+    pub fn extension(&self) -> Option<String> {
+        let (name, extension) = self.name.rsplit_once('.')?;
+        (!name.is_empty() && !extension.is_empty()).then(|| extension.to_lowercase())
+    }
+
+    /// The MIME type guessed from [UploadFile::extension] (application/octet-stream if it is unknown)
+    pub fn mime_type(&self) -> String {
+        self.extension()
+            .and_then(|extension| mime_guess::from_ext(&extension).first())
+            .unwrap_or(mime_guess::mime::APPLICATION_OCTET_STREAM)
+            .to_string()
+    }
+
+    /// Converts the file into a multipart part that reports its progress to `tracker` and stops if the upload is cancelled
+    fn into_part(self, file: usize, tracker: &UploadTracker) -> Part {
+        const CHUNK_SIZE: usize = 64 * 1024;
+
+        let size = self.size();
+        let mime_type = self.mime_type();
+        let chunks = match self.source {
+            UploadSource::Bytes(bytes) => stream::iter(
+                bytes
+                    .chunks(CHUNK_SIZE)
+                    .map(|chunk| Ok(chunk.to_vec()))
+                    .collect::<Vec<_>>(),
+            )
+            .boxed(),
+            UploadSource::Reader { reader, .. } => {
+                stream::try_unfold(reader, |mut reader| async move {
+                    let mut buffer = vec![0; CHUNK_SIZE];
+                    let read = reader.read(&mut buffer).await?;
+                    if read == 0 {
+                        return Ok::<_, std::io::Error>(None);
+                    }
+                    buffer.truncate(read);
+                    Ok(Some((buffer, reader)))
+                })
+                .boxed()
+            }
+        };
+
+        let name = self.name.to_owned();
+        let tracker = tracker.to_owned();
+        let mut sent = 0;
+        let chunks = chunks.map(move |chunk| {
+            if tracker.is_cancelled() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "upload cancelled",
+                ));
+            }
+
+            let chunk = chunk?;
+            sent += chunk.len() as u64;
+            let total_sent = tracker
+                .total_sent
+                .fetch_add(chunk.len() as u64, Ordering::Relaxed)
+                + chunk.len() as u64;
+            if let Some(progress) = &tracker.progress {
+                progress(UploadProgress {
+                    file,
+                    name: name.to_owned(),
+                    sent,
+                    size,
+                    total_sent,
+                    total: tracker.total,
+                });
+            }
+
+            Ok(chunk)
+        });
+
+        Part::stream_with_length(reqwest::Body::wrap_stream(chunks), size)
+            .file_name(self.name)
+            .mime_str(&mime_type)
+            .unwrap()
+    }
+}
+
+/// A progress event of [LessonUpload::upload_files_with]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct UploadProgress {
+    /// The index of the file in the uploaded [Vec]
+    pub file: usize,
+    pub name: String,
+    /// Bytes of this file that were sent so far
+    pub sent: u64,
+    /// The size of this file
+    pub size: u64,
+    /// Bytes of all files that were sent so far
+    pub total_sent: u64,
+    /// The size of all files
+    pub total: u64,
+}
+
+/// Cancels a running upload (see [UploadOptions::cancellation]) <br>
+/// Clones share the same state, so one clone can be kept to cancel while the other one is passed to the upload
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    inner: Arc<CancellationState>,
+}
+
+#[derive(Debug, Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Waits until [CancellationToken::cancel] is called
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Options for [LessonUpload::upload_files_with]
+#[derive(Clone, Default)]
+pub struct UploadOptions {
+    /// Called every time a chunk of a file is handed to the connection <br>
+    /// To receive the events through a channel, send them from inside the callback
+    pub progress: Option<Arc<dyn Fn(UploadProgress) + Send + Sync>>,
+    /// Aborts the request as soon as the token is cancelled
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl std::fmt::Debug for UploadOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UploadOptions")
+            .field("progress", &self.progress.is_some())
+            .field("cancellation", &self.cancellation)
+            .finish()
+    }
+}
+
+/// The shared state of all files of one [LessonUpload::upload_files_with] call
+#[derive(Clone)]
+struct UploadTracker {
+    progress: Option<Arc<dyn Fn(UploadProgress) + Send + Sync>>,
+    cancellation: Option<CancellationToken>,
+    total: u64,
+    total_sent: Arc<AtomicU64>,
+}
+
+impl UploadTracker {
+    fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(|cancellation| cancellation.is_cancelled())
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct LessonMark {
     pub name: String,
     pub date: DateTime<Utc>,
+    /// The mark as shown by Lanis
     pub mark: String,
+    /// The parsed [LessonMark::mark] (None if it isn't a grade, e.g. "-")
+    pub grade: Option<Grade>,
+    /// The heading of the group the mark belongs to (if the table is grouped)
+    pub category: Option<String>,
     pub comment: Option<String>,
 }
 
+/// The two grading scales used in Hessen
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum GradeScale {
+    /// 0 - 15 points (Oberstufe)
+    Points,
+    /// 1 - 6 with +/-
+    Marks,
+}
+
+impl GradeScale {
+    /// Guesses the scale of a course from all of its marks <br>
+    /// Marks from 1 to 6 without +/- could be both, in this case [GradeScale::Marks] is used
+    pub fn detect(marks: &[&str]) -> Self {
+        let points = marks.iter().any(|mark| {
+            mark.trim()
+                .parse::<u8>()
+                .is_ok_and(|value| value == 0 || (7..=15).contains(&value))
+        });
+        if points {
+            GradeScale::Points
+        } else {
+            GradeScale::Marks
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum GradeModifier {
+    Plus,
+    None,
+    Minus,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum Grade {
+    /// 0 - 15
+    Points(u8),
+    /// 1 - 6
+    Mark(u8, GradeModifier),
+}
+
+impl Grade {
+    /// Parses a mark like "11", "2+" or "3-" <br>
+    /// `scale` decides how marks without +/- from 1 to 6 are read
+    pub fn parse(text: &str, scale: GradeScale) -> Option<Self> {
+        let text = text.trim();
+        let (number, modifier) = match text.strip_suffix('+') {
+            Some(number) => (number, GradeModifier::Plus),
+            None => match text.strip_suffix('-') {
+                Some(number) => (number, GradeModifier::Minus),
+                None => (text, GradeModifier::None),
+            },
+        };
+        let value = number.trim().parse::<u8>().ok()?;
+
+        match (scale, modifier) {
+            (GradeScale::Points, GradeModifier::None) if value <= 15 => Some(Grade::Points(value)),
+            (_, _) if (1..=6).contains(&value) => Some(Grade::Mark(value, modifier)),
+            _ => None,
+        }
+    }
+
+    /// The grade in points (0 - 15), marks are converted with the usual table (1+ = 15, 1 = 14, ..., 6 = 0)
+    pub fn points(&self) -> u8 {
+        match self {
+            Grade::Points(points) => *points,
+            Grade::Mark(6, _) => 0,
+            Grade::Mark(value, modifier) => {
+                let points = 17 - 3 * value.clamp(&1, &5);
+                match modifier {
+                    GradeModifier::Plus => points + 1,
+                    GradeModifier::None => points,
+                    GradeModifier::Minus => points - 1,
+                }
+            }
+        }
+    }
+
+    /// Converts the grade to the given scale
+    pub fn to_scale(&self, scale: GradeScale) -> Self {
+        let points = self.points();
+        match scale {
+            GradeScale::Points => Grade::Points(points),
+            GradeScale::Marks if points == 0 => Grade::Mark(6, GradeModifier::None),
+            GradeScale::Marks => {
+                let value = (17 - points as i32 + 1) / 3;
+                let modifier = match (17 - points as i32) - 3 * value {
+                    -1 => GradeModifier::Plus,
+                    1 => GradeModifier::Minus,
+                    _ => GradeModifier::None,
+                };
+                Grade::Mark(value as u8, modifier)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Grade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Grade::Points(points) => write!(f, "{}", points),
+            Grade::Mark(value, GradeModifier::Plus) => write!(f, "{}+", value),
+            Grade::Mark(value, GradeModifier::None) => write!(f, "{}", value),
+            Grade::Mark(value, GradeModifier::Minus) => write!(f, "{}-", value),
+        }
+    }
+}
+
+/// Converts an average in points to the 1 - 6 scale (`(17 - points) / 3`, capped at 6)
+pub fn points_to_mark_average(points: f64) -> f64 {
+    ((17.0 - points) / 3.0).min(6.0)
+}
+
+/// Converts an average on the 1 - 6 scale to points
+pub fn mark_to_points_average(mark: f64) -> f64 {
+    (17.0 - 3.0 * mark).clamp(0.0, 15.0)
+}
+
+/// Weights of marks by their category or name (compared case-insensitive) <br>
+/// Marks without a weight count with 1
+pub type MarkWeights = BTreeMap<String, u32>;
+
+/// Calculates the weighted average of the given marks in points <br>
+/// Returns [None] if there are no (weighted) grades
+pub fn average_points(marks: &[LessonMark], weights: &MarkWeights) -> Option<f64> {
+    let weight = |mark: &LessonMark| {
+        weights
+            .iter()
+            .find(|(key, _)| {
+                mark.category
+                    .as_ref()
+                    .is_some_and(|category| category.eq_ignore_ascii_case(key))
+                    || mark.name.eq_ignore_ascii_case(key)
+            })
+            .map(|(_, weight)| *weight)
+            .unwrap_or(1)
+    };
+
+    let mut sum = 0.0;
+    let mut total_weight = 0;
+    for mark in marks {
+        if let Some(grade) = mark.grade {
+            let weight = weight(mark);
+            sum += grade.points() as f64 * weight as f64;
+            total_weight += weight;
+        }
+    }
+
+    if total_weight == 0 {
+        None
+    } else {
+        Some(sum / total_weight as f64)
+    }
+}
+
+/// Calculates the average in points over all courses (every course with marks counts the same)
+pub fn overall_average_points(lessons: &[Lesson], weights: &MarkWeights) -> Option<f64> {
+    let averages = lessons
+        .iter()
+        .filter_map(|lesson| lesson.average_points(weights))
+        .collect::<Vec<_>>();
+
+    if averages.is_empty() {
+        None
+    } else {
+        Some(averages.iter().sum::<f64>() / averages.len() as f64)
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
 pub struct LessonExam {
+    /// The date as shown by Lanis (e.g. "12.03.2024")
     pub date: String,
+    /// [LessonExam::date] parsed <br>
+    /// None if Lanis didn't provide a valid date
+    pub day: Option<NaiveDate>,
+    /// Only available if the time is part of the exam text (e.g. "08:00 - 09:30")
+    pub start: Option<NaiveTime>,
+    /// Only available if the time is part of the exam text
+    pub end: Option<NaiveTime>,
     pub name: String,
     pub finished: bool,
 }
 
+/// A time or time range at the start of an exam text (e.g. "08:00 - 09:30 Uhr")
+static EXAM_TIME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?:ab\s+)?(\d{1,2}[:.]\d{2})(?:\s*(?:-|–|bis)\s*(\d{1,2}[:.]\d{2}))?(?:\s*Uhr)?\b\s*",
+    )
+    .unwrap()
+});
+
+/// The duration of an exam (e.g. "(90 min)" or "2 Std.")
+static EXAM_DURATION_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\(?(\d+)\s*(min|Min\.?|Minuten|Std\.?|Stunden)\)?").unwrap());
+
+impl LessonExam {
+    /// Parses the text of an exam entry (e.g. "12.03.2024 08:00 - 09:30 Uhr Klausur 1")
+    pub(crate) fn parse(text: &str, finished: bool) -> Self {
+        let date = text
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let day = NaiveDate::parse_from_str(date.trim_end_matches(','), "%d.%m.%Y").ok();
+
+        let mut name = text
+            .trim()
+            .strip_prefix(date.as_str())
+            .unwrap_or(text)
+            .trim()
+            .to_string();
+        let (mut start, mut end) = (None, None);
+
+        if let Some(captures) = EXAM_TIME_REGEX.captures(&name) {
+            let parse_time =
+                |time: &str| NaiveTime::parse_from_str(&time.replace('.', ":"), "%H:%M").ok();
+            start = captures.get(1).and_then(|time| parse_time(time.as_str()));
+            end = captures.get(2).and_then(|time| parse_time(time.as_str()));
+            if start.is_some() {
+                name = name[captures.get(0).unwrap().end()..].trim().to_string();
+            }
+        }
+
+        // Durations like "(90 min)" or "2 Std." if no end time is given
+        if let (Some(start_time), None) = (start, end) {
+            if let Some(captures) = EXAM_DURATION_REGEX.captures(&name) {
+                let amount = captures[1].parse::<i64>().unwrap_or_default();
+                let minutes = if captures[2].starts_with('S') {
+                    amount * 60
+                } else {
+                    amount
+                };
+                end = Some(start_time + TimeDelta::minutes(minutes));
+                name = EXAM_DURATION_REGEX.replace(&name, "").trim().to_string();
+            }
+        }
+
+        Self {
+            date,
+            day,
+            start,
+            end,
+            name: name.split_whitespace().collect::<Vec<_>>().join(" "),
+            finished,
+        }
+    }
+
+    /// The duration of the exam (only if [LessonExam::start] and [LessonExam::end] are known)
+    pub fn duration(&self) -> Option<TimeDelta> {
+        Some(self.end? - self.start?)
+    }
+}
+
+/// Where an [ExamScheduleEntry] comes from
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ExamSource {
+    /// From [Lesson::exams], contains the [Lesson::id]
+    Lesson(i32),
+    /// From [crate::modules::calendar::get_entries], contains the [CalendarEntry::id]
+    Calendar(String),
+}
+
+/// One exam of the schedule returned by [exam_schedule]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ExamScheduleEntry {
+    pub day: NaiveDate,
+    pub start: Option<NaiveTime>,
+    pub end: Option<NaiveTime>,
+    pub name: String,
+    /// The course name (None for calendar entries without a matching course)
+    pub course: Option<String>,
+    pub teacher: Option<String>,
+    pub finished: bool,
+    pub source: ExamSource,
+}
+
+/// Collects the exams of all given courses into one chronologically sorted schedule <br>
+/// Exams are only available after [Lesson::set_data] (see [load_all]), exams without a valid date are left out
+pub fn exam_schedule(lessons: &[Lesson]) -> Vec<ExamScheduleEntry> {
+    let mut schedule = lessons
+        .iter()
+        .flat_map(|lesson| {
+            lesson.exams.iter().flatten().filter_map(move |exam| {
+                Some(ExamScheduleEntry {
+                    day: exam.day?,
+                    start: exam.start,
+                    end: exam.end,
+                    name: exam.name.to_owned(),
+                    course: Some(lesson.name.to_owned()),
+                    teacher: Some(lesson.teacher.to_owned()).filter(|teacher| !teacher.is_empty()),
+                    finished: exam.finished,
+                    source: ExamSource::Lesson(lesson.id),
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+    schedule.sort_by_key(|entry| (entry.day, entry.start));
+
+    schedule
+}
+
+/// Like [exam_schedule] but also merges exam events of the calendar between `from` and `to` (inclusive) <br>
+/// Calendar entries count as exams if the title or category contains "Klausur", "Arbeit", "Prüfung" or "Leistungskontrolle".
+/// Entries that are already known from a course (same day and course or exam name in the title) are skipped
+pub async fn exam_schedule_with_calendar(
+    lessons: &[Lesson],
+    from: NaiveDate,
+    to: NaiveDate,
+    client: &Client,
+) -> Result<Vec<ExamScheduleEntry>, Error> {
+    let entries = get_entries(from, to, None, client).await?;
+    let mut schedule = exam_schedule(lessons)
+        .into_iter()
+        .filter(|entry| entry.day >= from && entry.day <= to)
+        .collect::<Vec<_>>();
+    merge_calendar_exams(&mut schedule, lessons, &entries);
+
+    Ok(schedule)
+}
+
+/// Adds the exam events of `entries` to `schedule` and keeps it sorted
+pub(crate) fn merge_calendar_exams(
+    schedule: &mut Vec<ExamScheduleEntry>,
+    lessons: &[Lesson],
+    entries: &[CalendarEntry],
+) {
+    const KEYWORDS: [&str; 4] = ["klausur", "arbeit", "prüfung", "leistungskontrolle"];
+
+    for entry in entries {
+        let title = entry.title.to_lowercase();
+        let category = entry
+            .category
+            .as_ref()
+            .map(|category| category.name.to_lowercase())
+            .unwrap_or_default();
+        if !KEYWORDS
+            .iter()
+            .any(|keyword| title.contains(keyword) || category.contains(keyword))
+        {
+            continue;
+        }
+
+        let start = datetime_to_local(&entry.start);
+        let end = datetime_to_local(&entry.end);
+        let day = start.date_naive();
+
+        let known = schedule.iter().any(|exam| {
+            exam.day == day
+                && [exam.course.as_ref(), Some(&exam.name)]
+                    .into_iter()
+                    .flatten()
+                    .any(|name| !name.is_empty() && title.contains(&name.to_lowercase()))
+        });
+        if known {
+            continue;
+        }
+
+        // Calendar events of exams span the whole day if no time is known
+        let whole_day = start.time() == NaiveTime::MIN && (end - start) >= TimeDelta::hours(23);
+        let lesson = lessons.iter().find(|lesson| {
+            entry
+                .study_group
+                .as_ref()
+                .is_some_and(|group| group.name.eq_ignore_ascii_case(&lesson.name))
+                || title.contains(&lesson.name.to_lowercase())
+        });
+
+        schedule.push(ExamScheduleEntry {
+            day,
+            start: (!whole_day).then_some(start.time()),
+            end: (!whole_day).then_some(end.time()),
+            name: entry.title.to_owned(),
+            course: lesson.map(|lesson| lesson.name.to_owned()),
+            teacher: lesson
+                .map(|lesson| lesson.teacher.to_owned())
+                .filter(|teacher| !teacher.is_empty()),
+            finished: end < now_local(),
+            source: ExamSource::Calendar(entry.id.to_owned()),
+        });
+    }
+
+    schedule.sort_by_key(|entry| (entry.day, entry.start));
+}
+
 impl Lesson {
+    /// The weighted average of [Lesson::marks] in points (see [average_points])
+    pub fn average_points(&self, weights: &MarkWeights) -> Option<f64> {
+        average_points(self.marks.as_deref().unwrap_or_default(), weights)
+    }
+
+    /// The typed attendance of the current half-year from the overview (see [Lesson::attendances])
+    pub fn attendance(&self) -> Attendance {
+        Attendance::from_overview(&self.attendances)
+    }
+
+    /// Loads a course directly by its id (e.g. from a deep link or a notification) <br>
+    /// This also works for courses that aren't on the current overview anymore.
+    /// [Lesson::attendances] are only available on the overview and stay empty, [Lesson::entry_latest] is the newest entry of the history
+    pub async fn fetch(id: i32, account: &Account) -> Result<Lesson, Error> {
+        let mut lesson = Lesson {
+            id,
+            url: format!("meinunterricht.php?a=sus_view&id={}", id),
+            name: String::new(),
+            teacher: String::new(),
+            teacher_short: None,
+            subject_short: None,
+            attendances: BTreeMap::new(),
+            entry_latest: None,
+            entries: None,
+            marks: None,
+            exams: None,
+        };
+        lesson.set_data(account).await?;
+        lesson.entry_latest = lesson
+            .entries
+            .iter()
+            .flatten()
+            .max_by_key(|entry| entry.date)
+            .cloned();
+
+        Ok(lesson)
+    }
+
+    /// Sets the name and the teacher from the heading of a course page
+    fn set_header(&mut self, document: &Html) {
+        let heading_selector = Selector::parse("h1").unwrap();
+        if let Some(heading) = document.select(&heading_selector).next() {
+            self.name = heading
+                .text()
+                .collect::<String>()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+
+        // The teacher is shown like on the overview: "Max Mustermann (MUS)"
+        let title_selector = Selector::parse("#content [title]").unwrap();
+        if let Some((teacher, short)) = document
+            .select(&title_selector)
+            .filter_map(|element| element.value().attr("title"))
+            .find_map(split_teacher_name)
+        {
+            self.teacher = teacher;
+            self.teacher_short = Some(short);
+        }
+    }
+
     /**
      *  Sets the data for a lesson. This data includes: <br>
      *  Entries history, marks and class tests
@@ -158,11 +1255,24 @@ impl Lesson {
                 .await;
                 let document = Html::parse_document(&document);
 
+                // Only known if the lesson comes from the overview (see [Lesson::fetch])
+                if self.name.is_empty() {
+                    self.set_header(&document);
+                }
+
                 let mut history: Vec<LessonEntry> = vec![];
 
                 let history_doc_selector = Selector::parse("#history").unwrap();
                 let history_doc = document.select(&history_doc_selector);
-                let history_doc = history_doc.clone().next().unwrap().html();
+                let history_doc = history_doc
+                    .clone()
+                    .next()
+                    .ok_or(Error::Parsing(format!(
+                        "no course history found on '{}{}', the course may not exist or isn't accessible",
+                        URL::BASE,
+                        &self.url
+                    )))?
+                    .html();
                 let mut history_doc = Html::parse_document(&history_doc);
 
                 let history_table_rows_selector = Selector::parse("table>tbody>tr").unwrap();
@@ -318,6 +1428,23 @@ impl Lesson {
                         }
                     };
 
+                    let date = row
+                        .child_elements()
+                        .nth(0)
+                        .unwrap()
+                        .text()
+                        .collect::<String>()
+                        .split("\n")
+                        .nth(0)
+                        .unwrap()
+                        .trim()
+                        .to_string();
+                    let date = date_time_string_to_datetime(date.as_str(), "02:00:00")
+                        .map_err(|e| {
+                            Error::DateTime(format!("failed to convert date to DateTime '{:?}'", e))
+                        })?
+                        .to_utc();
+
                     let uploads: Option<Vec<LessonUpload>> = {
                         let upload_groups = row
                             .child_elements()
@@ -380,22 +1507,14 @@ impl Lesson {
                                     let text = text.replace(" den", "").trim().to_string();
                                     let text = text.replace(" Uhr", "").trim().to_string();
                                     let split = text.split(" ");
-                                    let date = format!(
-                                        "{}{}",
-                                        split.clone().nth(1).unwrap_or_default(),
-                                        chrono::Local::now().year(),
-                                    );
-                                    let time = format!("{}:00", split.last().unwrap_or_default());
-                                    println!("text is: {}", text);
+                                    let time =
+                                        format!("{}:00", split.clone().last().unwrap_or_default());
 
-                                    date_time_string_to_datetime(date.as_str(), time.as_str())
-                                        .map_err(|e| {
-                                            Error::DateTime(format!(
-                                                "failed to convert date to DateTime '{:?}'",
-                                                e
-                                            ))
-                                        })?
-                                        .to_utc()
+                                    upload_deadline(
+                                        split.clone().nth(1).unwrap_or_default(),
+                                        &time,
+                                        &date,
+                                    )?
                                 };
                                 let id = url.split("&id=").last().unwrap().parse::<i32>().unwrap();
 
@@ -474,22 +1593,10 @@ impl Lesson {
                         }
                     };
 
-                    let date = row
+                    let presence = row
                         .child_elements()
-                        .nth(0)
-                        .unwrap()
-                        .text()
-                        .collect::<String>()
-                        .split("\n")
-                        .nth(0)
-                        .unwrap()
-                        .trim()
-                        .to_string();
-                    let date = date_time_string_to_datetime(date.as_str(), "02:00:00")
-                        .map_err(|e| {
-                            Error::DateTime(format!("failed to convert date to DateTime '{:?}'", e))
-                        })?
-                        .to_utc();
+                        .nth(2)
+                        .and_then(|element| Presence::parse(&element.text().collect::<String>()));
                     let school_hours = {
                         let mut school_hours = vec![];
 
@@ -550,6 +1657,7 @@ impl Lesson {
                                 None
                             }
                         },
+                        presence,
                     })
                 }
                 self.entries = Some(history);
@@ -578,8 +1686,15 @@ impl Lesson {
                 let marks_table_rows = marks_doc.select(&marks_table_rows_selector);
 
                 let mut marks = vec![];
+                let mut category = None;
 
                 for row in marks_table_rows {
+                    if row.child_elements().count() == 1 {
+                        let heading = row.text().collect::<String>().trim().to_string();
+                        if !heading.is_empty() {
+                            category = Some(heading);
+                        }
+                    }
                     if row.child_elements().count() == 3 {
                         let name = row
                             .child_elements()
@@ -602,7 +1717,7 @@ impl Lesson {
                                     .unwrap_or_default()
                                     .1
                                     .trim(),
-                                chrono::Local::now().year()
+                                now_local().year()
                             ),
                             "02:00:00",
                         )
@@ -648,10 +1763,21 @@ impl Lesson {
                             name,
                             date,
                             mark,
+                            grade: None,
+                            category: category.to_owned(),
                             comment,
                         });
                     }
                 }
+                let scale = GradeScale::detect(
+                    &marks
+                        .iter()
+                        .map(|mark| mark.mark.as_str())
+                        .collect::<Vec<_>>(),
+                );
+                for mark in marks.iter_mut() {
+                    mark.grade = Grade::parse(&mark.mark, scale);
+                }
                 self.marks = Some(marks);
 
                 // Exams
@@ -704,28 +1830,10 @@ impl Lesson {
                                     result = result.replace("\n", "").trim().to_string();
                                     result
                                 };
-                                let split = exam.split(" ");
-                                let date = split.clone().nth(0).unwrap().trim().to_string();
-                                let name = {
-                                    let mut result = "".to_string();
-                                    for i in 1..split.clone().count() {
-                                        result =
-                                            format!("{} {}", result, split.clone().nth(i).unwrap());
-                                    }
-                                    result.trim().to_string()
-                                };
-
-                                exams.push(LessonExam {
-                                    date,
-                                    name,
-                                    finished: {
-                                        if title == "Alle Leistungskontrolle(n)" {
-                                            true
-                                        } else {
-                                            false
-                                        }
-                                    },
-                                });
+                                exams.push(LessonExam::parse(
+                                    &exam,
+                                    title == "Alle Leistungskontrolle(n)",
+                                ));
                             }
                         }
                     }
@@ -745,6 +1853,11 @@ impl Lesson {
 }
 
 impl Homework {
+    /// The description as [RichText] (see [RichText::parse])
+    pub fn rich_description(&self) -> RichText {
+        RichText::parse(&self.description)
+    }
+
     pub async fn set_homework(
         &mut self,
         state: bool,
@@ -857,17 +1970,13 @@ impl LessonUpload {
                     Selector::parse("i.fa.fa-eye.fa-fw + span.label").unwrap();
                 let visibility_selector_1 =
                     Selector::parse("i.fa.fa-eye-slash.fa-fw + span.label").unwrap();
-                let visibility = requirements
-                    .select(&visibility_selector_0)
-                    .nth(0)
-                    .and_then(|e| Some(e.text().collect::<String>().trim().to_string()))
-                    .or_else(|| {
-                        requirements
-                            .select(&visibility_selector_1)
-                            .nth(0)
-                            .and_then(|e| Some(e.text().collect::<String>().trim().to_string()))
-                            .or_else(|| None)
-                    });
+                let visibility = if requirements.select(&visibility_selector_0).next().is_some() {
+                    Some(UploadVisibility::Public)
+                } else if requirements.select(&visibility_selector_1).next().is_some() {
+                    Some(UploadVisibility::Private)
+                } else {
+                    None
+                };
 
                 let automatic_deletion_selector =
                     Selector::parse("i.fa.fa-trash-o.fa-fw + span.label.label-info").unwrap();
@@ -1040,7 +2149,9 @@ impl LessonUpload {
 
                 let start = start.await;
                 let end = end.await;
-                let automatic_deletion = automatic_deletion.await;
+                let automatic_deletion = automatic_deletion
+                    .await
+                    .map(|text| AutomaticDeletion::parse(&text));
 
                 async fn parse_date_time(s: String) -> Result<DateTime<Utc>, Error> {
                     let ymd = format!("{}", &s.split(" ").nth(2).unwrap());
@@ -1099,61 +2210,102 @@ impl LessonUpload {
         }
     }
 
-    /// Takes a vector of file paths (max. 5) and uploads these files to Lanis. <br>
+    /// Takes a vector of file paths and uploads these files to Lanis (see [LessonUpload::upload_files]) <br>
     /// [LessonUpload::get_info] must be called before calling this function
     pub async fn upload(
         &self,
         files: Vec<&Path>,
         client: &Client,
     ) -> Result<Vec<LessonUploadFileStatus>, Error> {
-        if self.info.is_none() {
-            return Err(Error::Parsing("No info found in lessons!".to_string()));
+        let mut upload_files = Vec::with_capacity(files.len());
+        for path in files {
+            upload_files.push(UploadFile::from_path(path).await?);
         }
 
-        if files.is_empty() {
-            return Err(Error::Parsing("No files found in lessons!".to_string()));
-        }
+        self.upload_files(upload_files, client).await
+    }
+
+    /// Uploads the given files to Lanis (see [LessonUpload::upload_files_with])
+    pub async fn upload_files(
+        &self,
+        files: Vec<UploadFile>,
+        client: &Client,
+    ) -> Result<Vec<LessonUploadFileStatus>, Error> {
+        self.upload_files_with(files, &UploadOptions::default(), client)
+            .await
+    }
 
-        let upload_info = self.info.clone().unwrap();
+    /// Uploads the given files to Lanis and reports the progress to [UploadOptions::progress] <br>
+    /// All files are checked with [LessonUploadInfo::validate] before anything is sent.
+    /// More than [MAX_FILES_PER_UPLOAD] files are split into several requests (if [LessonUploadInfo::multiple_files] allows it).
+    /// If a later request fails (or the upload is cancelled) the files of the earlier requests are already uploaded. <br>
+    /// [LessonUpload::get_info] must be called before calling this function
+    pub async fn upload_files_with(
+        &self,
+        files: Vec<UploadFile>,
+        options: &UploadOptions,
+        client: &Client,
+    ) -> Result<Vec<LessonUploadFileStatus>, Error> {
+        let upload_info = self
+            .info
+            .as_ref()
+            .ok_or(Error::LessonUploadError(LessonUploadError::NoInfo))?;
+        let (Some(course_id), Some(entry_id)) = (upload_info.course_id, upload_info.entry_id)
+        else {
+            return Err(Error::LessonUploadError(LessonUploadError::NoDetailedInfo));
+        };
+
+        upload_info.validate(&files).await?;
+
+        let tracker = UploadTracker {
+            progress: options.progress.to_owned(),
+            cancellation: options.cancellation.to_owned(),
+            total: files.iter().map(|file| file.size()).sum(),
+            total_sent: Arc::new(AtomicU64::new(0)),
+        };
+
+        let mut status_messages = vec![];
+        let mut files = files.into_iter().enumerate().peekable();
+        while files.peek().is_some() {
+            if tracker.is_cancelled() {
+                return Err(Error::LessonUploadError(LessonUploadError::Cancelled));
+            }
+
+            let chunk = files
+                .by_ref()
+                .take(MAX_FILES_PER_UPLOAD)
+                .collect::<Vec<_>>();
+            status_messages.extend(
+                self.send_files(course_id, entry_id, chunk, &tracker, client)
+                    .await?,
+            );
+        }
 
-        let course_id = upload_info.course_id.unwrap();
-        let entry_id = upload_info.entry_id.unwrap();
+        Ok(status_messages)
+    }
 
-        let form = reqwest::multipart::Form::new()
+    /// Sends one upload request with up to [MAX_FILES_PER_UPLOAD] files (with their index)
+    async fn send_files(
+        &self,
+        course_id: i32,
+        entry_id: i32,
+        files: Vec<(usize, UploadFile)>,
+        tracker: &UploadTracker,
+        client: &Client,
+    ) -> Result<Vec<LessonUploadFileStatus>, Error> {
+        let mut form = reqwest::multipart::Form::new()
             .part("a", Part::text("sus_abgabe"))
             .part("b", Part::text(course_id.to_string()))
             .part("e", Part::text(entry_id.to_string()))
-            .part("id", Part::text(self.id.to_string()))
-            .part("file1", {
-                match files.get(0) {
-                    Some(path) => Part::file(path).await.unwrap(),
-                    None => Part::bytes(&[]),
-                }
-            })
-            .part("file2", {
-                match files.get(1) {
-                    Some(path) => Part::file(path).await.unwrap(),
-                    None => Part::bytes(&[]),
-                }
-            })
-            .part("file3", {
-                match files.get(2) {
-                    Some(path) => Part::file(path).await.unwrap(),
-                    None => Part::bytes(&[]),
-                }
-            })
-            .part("file4", {
-                match files.get(3) {
-                    Some(path) => Part::file(path).await.unwrap(),
-                    None => Part::bytes(&[]),
-                }
-            })
-            .part("file5", {
-                match files.get(4) {
-                    Some(path) => Part::file(path).await.unwrap(),
-                    None => Part::bytes(&[]),
-                }
-            });
+            .part("id", Part::text(self.id.to_string()));
+        let mut files = files.into_iter();
+        for index in 1..=MAX_FILES_PER_UPLOAD {
+            let part = match files.next() {
+                Some((file, upload_file)) => upload_file.into_part(file, tracker),
+                None => Part::bytes(&[]),
+            };
+            form = form.part(format!("file{}", index), part);
+        }
 
         let mut headers = HeaderMap::new();
         headers.insert("Accept", "*/*".parse().unwrap());
@@ -1168,13 +2320,26 @@ impl LessonUpload {
         //    message: Some("Same again".to_string()),
         //}]);
 
-        match client
+        let request = client
             .post(URL::MEIN_UNTERRICHT)
             .headers(headers)
             .multipart(form)
-            .send()
-            .await
-        {
+            .send();
+        // Dropping the request aborts the connection
+        let result = match &tracker.cancellation {
+            Some(cancellation) => tokio::select! {
+                result = request => result,
+                _ = cancellation.cancelled() => {
+                    return Err(Error::LessonUploadError(LessonUploadError::Cancelled))
+                }
+            },
+            None => request.await,
+        };
+        if result.is_err() && tracker.is_cancelled() {
+            return Err(Error::LessonUploadError(LessonUploadError::Cancelled));
+        }
+
+        match result {
             Ok(response) => {
                 let text = response.text().await.unwrap();
                 let document = Html::parse_document(&text);
@@ -1304,6 +2469,113 @@ impl LessonUpload {
             ))),
         }
     }
+
+    /// Replaces the own file with the given index (see [LessonUploadInfoOwnFile::index]) with `file` <br>
+    /// The new file is uploaded first and the old one is only deleted after the new one shows up in [LessonUploadInfo::own_files].
+    /// If the upload or the verification fails the old file is kept and an [Err] is returned.
+    /// If only the deletion fails both files stay submitted and the error is in [ReplaceReport::deletion]. <br>
+    /// [LessonUpload::info] is updated with the info after the replacement
+    pub async fn replace(
+        &mut self,
+        old_index: i32,
+        file: UploadFile,
+        account: &Account,
+    ) -> Result<ReplaceReport, Error> {
+        let client = &account.client;
+
+        let before = self.get_info(client).await?;
+        let old_file = before
+            .own_files
+            .iter()
+            .find(|own_file| own_file.index == old_index)
+            .ok_or(Error::InvalidInput(format!(
+                "no own file with index {} found",
+                old_index
+            )))?
+            .to_owned();
+        self.info = Some(before.to_owned());
+
+        let upload = self.upload_files(vec![file], client).await?;
+
+        let mut after = self.get_info(client).await?;
+        let new_files = after
+            .own_files
+            .iter()
+            .filter(|own_file| {
+                !before
+                    .own_files
+                    .iter()
+                    .any(|known| known.index == own_file.index)
+            })
+            .collect::<Vec<_>>();
+        let new_file = new_files
+            .iter()
+            .find(|own_file| upload.iter().any(|status| status.name == own_file.name))
+            .or(new_files.first())
+            .map(|own_file| (*own_file).to_owned())
+            .ok_or(Error::LessonUploadError(LessonUploadError::NotVerified))?;
+        self.info = Some(after.to_owned());
+
+        let deletion = self.delete(&old_file.index, account).await;
+        if deletion.is_ok() {
+            after
+                .own_files
+                .retain(|own_file| own_file.index != old_file.index);
+            self.info = Some(after);
+        }
+
+        Ok(ReplaceReport {
+            upload,
+            old_file,
+            new_file,
+            deletion,
+        })
+    }
+}
+
+/// Parses a course folder card (as shown in `#mappen`) <br>
+/// Returns None if the card has no link to a course page
+fn parse_course_folder(card: ElementRef) -> Option<Lesson> {
+    let h2_selector = Selector::parse("h2").unwrap();
+    let button_selector = Selector::parse("div.btn-group > button").unwrap();
+    let link_selector = Selector::parse("a.btn.btn-primary").unwrap();
+
+    let url = card
+        .select(&link_selector)
+        .next()?
+        .value()
+        .attr("href")?
+        .to_string();
+    let id = url.split("id=").nth(1)?.parse::<i32>().ok()?;
+    let name = card
+        .select(&h2_selector)
+        .next()
+        .map(|h2| h2.text().collect::<String>().trim().to_string())
+        .unwrap_or_default();
+    let teacher = card
+        .select(&button_selector)
+        .next()
+        .and_then(|btn| btn.value().attr("title"))
+        .unwrap_or_default();
+    // The title looks like "Max Mustermann (MUS)"
+    let (teacher, teacher_short) = match split_teacher_name(teacher) {
+        Some((teacher, short)) => (teacher, Some(short)),
+        None => (teacher.trim().to_string(), None),
+    };
+
+    Some(Lesson {
+        id,
+        url,
+        name,
+        teacher,
+        teacher_short,
+        subject_short: None,
+        attendances: BTreeMap::new(),
+        entry_latest: None,
+        entries: None,
+        marks: None,
+        exams: None,
+    })
 }
 
 pub async fn get_lessons(account: &Account) -> Result<Vec<Lesson>, Error> {
@@ -1323,53 +2595,13 @@ pub async fn get_lessons(account: &Account) -> Result<Vec<Lesson>, Error> {
                     let document = Html::parse_document(&response);
                     let lesson_folders_selector = Selector::parse("#mappen").unwrap();
                     let row_selector = Selector::parse(".row").unwrap();
-                    let h2_selector = Selector::parse("h2").unwrap();
-                    let button_selector = Selector::parse("div.btn-group > button").unwrap();
-                    let link_selector = Selector::parse("a.btn.btn-primary").unwrap();
 
                     if let Some(lesson_folders) = document.select(&lesson_folders_selector).next() {
                         if let Some(row) = lesson_folders.select(&row_selector).next() {
-                            let mut lessons = Vec::new();
-                            for lesson in row.child_elements() {
-                                if let Some(url_element) = lesson.select(&link_selector).next() {
-                                    let url = url_element.value().attr("href").unwrap().to_string();
-                                    let id = url
-                                        .split("id=")
-                                        .nth(1)
-                                        .unwrap()
-                                        .to_string()
-                                        .parse::<i32>()
-                                        .unwrap();
-                                    let name = lesson
-                                        .select(&h2_selector)
-                                        .next()
-                                        .unwrap()
-                                        .text()
-                                        .collect::<String>()
-                                        .trim()
-                                        .to_string();
-                                    let teacher: String = lesson
-                                        .select(&button_selector)
-                                        .next()
-                                        .and_then(|btn| btn.value().attr("title"))
-                                        .map(|s| s.to_string())
-                                        .unwrap();
-                                    let teacher: String =
-                                        teacher.split(" (").next().unwrap().to_string();
-                                    lessons.push(Lesson {
-                                        id,
-                                        url,
-                                        name,
-                                        teacher,
-                                        teacher_short: None,
-                                        attendances: BTreeMap::new(),
-                                        entry_latest: None,
-                                        entries: None,
-                                        marks: None,
-                                        exams: None,
-                                    })
-                                }
-                            }
+                            let mut lessons = row
+                                .child_elements()
+                                .filter_map(parse_course_folder)
+                                .collect::<Vec<_>>();
 
                             // Get latest lesson entry
                             let school_classes_selector = Selector::parse("tr.printable").unwrap();
@@ -1481,8 +2713,11 @@ pub async fn get_lessons(account: &Account) -> Result<Vec<Lesson>, Error> {
                                             attachments: None,
                                             attachment_number: file_count,
                                             uploads: None,
+                                            presence: None,
                                         });
-                                        lesson.teacher_short = Some(teacher_short.to_owned());
+                                        if !teacher_short.is_empty() {
+                                            lesson.teacher_short = Some(teacher_short.to_owned());
+                                        }
                                     }
                                 }
                             }
@@ -1580,3 +2815,619 @@ pub async fn get_lessons(account: &Account) -> Result<Vec<Lesson>, Error> {
         ))),
     }
 }
+
+/// Options for [load_all]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct LoadOptions {
+    /// The maximum number of course pages that are requested at the same time
+    pub concurrent_requests: usize,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            concurrent_requests: 4,
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct LoadedLessons {
+    /// All courses, courses that failed to load are included without their data (see [Lesson::set_data])
+    pub lessons: Vec<Lesson>,
+    pub failures: Vec<LessonLoadFailure>,
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct LessonLoadFailure {
+    /// The id of the [Lesson] that failed to load
+    pub lesson_id: i32,
+    pub error: Error,
+}
+
+/// Gets all lessons and sets their data (see [Lesson::set_data]) concurrently <br>
+/// Only fails if the overview can't be loaded, errors of single courses are returned in [LoadedLessons::failures]
+pub async fn load_all(account: &Account, options: &LoadOptions) -> Result<LoadedLessons, Error> {
+    let lessons = get_lessons(account).await?;
+
+    Ok(load_lessons(lessons, account, options).await)
+}
+
+/// Sets the data of the given lessons concurrently (see [load_all])
+async fn load_lessons(
+    lessons: Vec<Lesson>,
+    account: &Account,
+    options: &LoadOptions,
+) -> LoadedLessons {
+    let results = stream::iter(lessons)
+        .map(|mut lesson| async move {
+            let result = lesson.set_data(account).await;
+            (lesson, result)
+        })
+        .buffered(options.concurrent_requests.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut loaded = LoadedLessons {
+        lessons: Vec::new(),
+        failures: Vec::new(),
+    };
+    for (lesson, result) in results {
+        if let Err(error) = result {
+            loaded.failures.push(LessonLoadFailure {
+                lesson_id: lesson.id,
+                error,
+            });
+        }
+        loaded.lessons.push(lesson);
+    }
+
+    loaded
+}
+
+/// The course folders of a past half-year
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ArchivedHalfYear {
+    /// The heading of the half-year as shown by Lanis (e.g. "1. Halbjahr 2023/24")
+    pub label: String,
+    /// None if the heading couldn't be parsed
+    pub half_year: Option<HalfYear>,
+    pub lessons: Vec<Lesson>,
+}
+
+/// The school year of an archive heading (e.g. "2023/24" or "2023/2024")
+static ARCHIVE_YEAR_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d{4})\s*/\s*(?:\d{4}|\d{2})").unwrap());
+
+/// The half of an archive heading (e.g. "1. Halbjahr" or "2. HJ")
+static ARCHIVE_HALF_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)([12])\s*\.?\s*(?:halbjahr|hj)").unwrap());
+
+impl ArchivedHalfYear {
+    /// Parses headings like "1. Halbjahr 2023/24", "Schuljahr 2023/2024 - 2. HJ" or "2023/24 1. Halbjahr"
+    pub(crate) fn parse_label(label: &str) -> Option<HalfYear> {
+        let school_year = ARCHIVE_YEAR_REGEX.captures(label)?[1].parse::<i32>().ok()?;
+        let half = ARCHIVE_HALF_REGEX.captures(label)?[1].parse::<u8>().ok()?;
+
+        Some(HalfYear { school_year, half })
+    }
+}
+
+/// Parses the archive of an overview page <br>
+/// Every heading inside of `#archiv` starts a new half-year, the course folders below it are parsed like the ones of `#mappen` <br>
+/// NOTE: The layout of `#archiv` is assumed to match `#mappen` and wasn't checked against a real archive page yet. If it differs the archive is empty
+pub(crate) fn parse_archive(document: &Html) -> Vec<ArchivedHalfYear> {
+    let archive_selector = Selector::parse("#archiv").unwrap();
+    let group_selector = Selector::parse("h3, h4, legend, .row").unwrap();
+
+    let mut half_years: Vec<ArchivedHalfYear> = Vec::new();
+    let Some(archive) = document.select(&archive_selector).next() else {
+        return half_years;
+    };
+
+    for element in archive.select(&group_selector) {
+        if !element.value().classes().any(|class| class == "row") {
+            let label = element
+                .text()
+                .collect::<String>()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            half_years.push(ArchivedHalfYear {
+                half_year: ArchivedHalfYear::parse_label(&label),
+                label,
+                lessons: Vec::new(),
+            });
+            continue;
+        }
+
+        // Rows inside of a course folder belong to that folder
+        let nested = element
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|ancestor| ancestor.value().classes().any(|class| class == "row"));
+        if nested {
+            continue;
+        }
+
+        let lessons = element.child_elements().filter_map(parse_course_folder);
+        match half_years.last_mut() {
+            Some(half_year) => half_year.lessons.extend(lessons),
+            None => half_years.push(ArchivedHalfYear {
+                label: String::new(),
+                half_year: None,
+                lessons: lessons.collect(),
+            }),
+        }
+    }
+
+    half_years.retain(|half_year| !half_year.lessons.is_empty());
+    half_years
+}
+
+/// Gets the course folders of past half-years, newest first <br>
+/// Like [get_lessons] only the overview is loaded, use [load_archive] or [Lesson::set_data] to get the entries, marks and attachments
+pub async fn get_archive(account: &Account) -> Result<Vec<ArchivedHalfYear>, Error> {
+    let unix_time = SystemTime::UNIX_EPOCH.elapsed().unwrap().as_millis();
+    let url = URL::BASE.to_owned() + &format!("meinunterricht.php?cacheBreaker={}", unix_time);
+
+    let response = account
+        .client
+        .get(&url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| Error::Network(format!("failed to get '{}' with error '{}'", url, e)))?;
+    let response = response
+        .text()
+        .await
+        .map_err(|e| Error::Parsing(format!("failed to parse response with error '{}'", e)))?;
+    let response = decrypt_lanis_encoded_tags(&response, &account.key_pair.public_key_string).await;
+    let document = Html::parse_document(&response);
+
+    // Lanis answers with a login or error page (and status 200) if the session expired
+    if document
+        .select(&Selector::parse("#mappen").unwrap())
+        .next()
+        .is_none()
+    {
+        return Err(Error::Parsing(format!(
+            "'{}' is not the overview of Mein Unterricht",
+            url
+        )));
+    }
+
+    let mut half_years = parse_archive(&document);
+    half_years.sort_by_key(|half_year| std::cmp::Reverse(half_year.half_year));
+
+    Ok(half_years)
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct LoadedArchive {
+    /// All archived half-years, courses that failed to load are included without their data (see [Lesson::set_data])
+    pub half_years: Vec<ArchivedHalfYear>,
+    pub failures: Vec<LessonLoadFailure>,
+}
+
+/// Gets the archive and sets the data of every archived course (see [load_all]) <br>
+/// Because the overview only shows the latest entry of current courses [Lesson::entry_latest] is set to the newest entry of the history
+pub async fn load_archive(
+    account: &Account,
+    options: &LoadOptions,
+) -> Result<LoadedArchive, Error> {
+    let mut archive = LoadedArchive {
+        half_years: get_archive(account).await?,
+        failures: Vec::new(),
+    };
+
+    for half_year in archive.half_years.iter_mut() {
+        let lessons = std::mem::take(&mut half_year.lessons);
+        let mut loaded = load_lessons(lessons, account, options).await;
+        for lesson in loaded.lessons.iter_mut() {
+            lesson.entry_latest = lesson
+                .entries
+                .iter()
+                .flatten()
+                .max_by_key(|entry| entry.date)
+                .cloned();
+        }
+        half_year.lessons = loaded.lessons;
+        archive.failures.append(&mut loaded.failures);
+    }
+
+    Ok(archive)
+}
+
+/// The result of [mirror_course_material]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct MirrorResult {
+    /// Files that were downloaded
+    pub downloaded: Vec<PathBuf>,
+    /// Files that were already downloaded and didn't change
+    pub skipped: Vec<PathBuf>,
+    pub failures: Vec<MirrorFailure>,
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct MirrorFailure {
+    pub path: PathBuf,
+    pub error: Error,
+}
+
+/// The state of [mirror_course_material]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct MirrorProgress {
+    /// The file that is currently downloaded
+    pub path: PathBuf,
+    /// The number of the current file (starting at 1)
+    pub file: usize,
+    pub files: usize,
+    pub download: DownloadProgress,
+}
+
+/// Parses the deadline of an upload from the overview (e.g. "12.01." and "23:59:00") <br>
+/// Lanis doesn't show the year, so it is taken from the date of the entry. A deadline before the entry is in the next year
+/// (e.g. an upload from December that is due in January)
+pub(crate) fn upload_deadline(
+    day_month: &str,
+    time: &str,
+    entry_date: &DateTime<Utc>,
+) -> Result<DateTime<Utc>, Error> {
+    let year = datetime_to_local(entry_date).year();
+    let parse = |year: i32| {
+        date_time_string_to_datetime(&format!("{}{}", day_month, year), time)
+            .map(|date| date.to_utc())
+            .map_err(|e| Error::DateTime(format!("failed to convert date to DateTime '{:?}'", e)))
+    };
+
+    let deadline = parse(year)?;
+    if datetime_to_local(&deadline).date_naive() < datetime_to_local(entry_date).date_naive() {
+        parse(year + 1)
+    } else {
+        Ok(deadline)
+    }
+}
+
+/// Returns the path of every attachment of the given courses inside of `root` <br>
+/// The structure is `<root>/<course>/<date>/<file>`. If a course has more than one entry on the same day the id of the entry is appended to the date
+pub fn course_material_paths(lessons: &[Lesson], root: &Path) -> Vec<(PathBuf, Attachment)> {
+    let mut paths = Vec::new();
+
+    for lesson in lessons {
+        let entries = lesson.entries.as_deref().unwrap_or_default();
+        let folder = root.join(sanitize_file_name(&lesson.name));
+
+        for entry in entries {
+            let date = datetime_to_local(&entry.date).date_naive();
+            let same_day = entries
+                .iter()
+                .filter(|other| datetime_to_local(&other.date).date_naive() == date)
+                .count();
+            let day_folder = if same_day > 1 {
+                format!("{}_{}", date.format("%Y-%m-%d"), entry.id)
+            } else {
+                date.format("%Y-%m-%d").to_string()
+            };
+
+            for attachment in entry.attachments.iter().flatten() {
+                paths.push((
+                    folder
+                        .join(&day_folder)
+                        .join(sanitize_file_name(&attachment.name)),
+                    attachment.to_owned(),
+                ));
+            }
+        }
+    }
+
+    paths
+}
+
+/// The name of the file in the root of a mirror that remembers which files were downloaded (see [mirror_course_material])
+pub const MIRROR_MANIFEST: &str = ".lanis-mirror.json";
+
+/// An entry of the [MIRROR_MANIFEST]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+struct MirrorManifestEntry {
+    url: String,
+    /// The (rounded) size shown by Lanis
+    size: u64,
+    /// The real size of the downloaded file
+    length: u64,
+}
+
+type MirrorManifest = BTreeMap<String, MirrorManifestEntry>;
+
+async fn load_mirror_manifest(path: &Path) -> MirrorManifest {
+    match tokio::fs::read(path).await {
+        Ok(content) => serde_json::from_slice(&content).unwrap_or_default(),
+        Err(_) => MirrorManifest::new(),
+    }
+}
+
+async fn save_mirror_manifest(path: &Path, manifest: &MirrorManifest) -> Result<(), Error> {
+    let content = serde_json::to_vec_pretty(manifest)
+        .map_err(|e| Error::Parsing(format!("failed to serialize mirror manifest '{}'", e)))?;
+
+    let temporary = path.with_extension("tmp");
+    tokio::fs::write(&temporary, content).await.map_err(|e| {
+        Error::FileSystem(format!(
+            "failed to write mirror manifest to '{}' with error '{}'",
+            temporary.display(),
+            e
+        ))
+    })?;
+    tokio::fs::rename(&temporary, path).await.map_err(|e| {
+        Error::FileSystem(format!(
+            "failed to move mirror manifest to '{}' with error '{}'",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Saves every attachment of every [LessonEntry] of the given courses into `root` (see [course_material_paths]) <br>
+/// Downloaded files are recorded in [MIRROR_MANIFEST] inside of `root`. A file is skipped if it is recorded with the same url and size as shown by Lanis and wasn't changed on disk,
+/// so this can be used to keep a mirror up to date. <br>
+/// The entries have to be loaded first (see [Lesson::set_data] or [load_all]). Errors of single files are returned in [MirrorResult::failures]
+pub async fn mirror_course_material<F>(
+    lessons: &[Lesson],
+    root: &Path,
+    client: &Client,
+    mut progress: F,
+) -> MirrorResult
+where
+    F: FnMut(MirrorProgress),
+{
+    let paths = course_material_paths(lessons, root);
+    let files = paths.len();
+    let mut result = MirrorResult::default();
+
+    let manifest_path = root.join(MIRROR_MANIFEST);
+    let mut manifest = load_mirror_manifest(&manifest_path).await;
+
+    for (index, (path, attachment)) in paths.into_iter().enumerate() {
+        let key = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .to_string();
+
+        let existing = tokio::fs::metadata(&path).await.ok();
+        let unchanged = manifest.get(&key).is_some_and(|entry| {
+            entry.url == attachment.url
+                && entry.size == attachment.size
+                && existing
+                    .as_ref()
+                    .is_some_and(|metadata| metadata.is_file() && metadata.len() == entry.length)
+        });
+        if unchanged {
+            result.skipped.push(path);
+            continue;
+        }
+
+        let download = attachment
+            .download(&path, client, |download| {
+                progress(MirrorProgress {
+                    path: path.to_owned(),
+                    file: index + 1,
+                    files,
+                    download,
+                })
+            })
+            .await;
+        match download {
+            Ok(length) => {
+                manifest.insert(
+                    key,
+                    MirrorManifestEntry {
+                        url: attachment.url.to_owned(),
+                        size: attachment.size,
+                        length,
+                    },
+                );
+                result.downloaded.push(path);
+            }
+            Err(error) => result.failures.push(MirrorFailure { path, error }),
+        }
+    }
+
+    if !result.downloaded.is_empty() {
+        if let Err(error) = save_mirror_manifest(&manifest_path, &manifest).await {
+            result.failures.push(MirrorFailure {
+                path: manifest_path,
+                error,
+            });
+        }
+    }
+
+    result
+}
+
+/// An open upload of [open_submissions]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct OpenSubmission {
+    pub lesson_id: i32,
+    pub course: String,
+    pub teacher: String,
+    pub entry_id: i32,
+    pub entry_title: String,
+    pub deadline: Option<DateTime<Utc>>,
+    pub state: SubmissionState,
+    pub upload: LessonUpload,
+}
+
+/// Collects all uploads of all courses that are still open (see [LessonUpload::state]), including ones that were already submitted <br>
+/// The result is sorted by deadline, uploads without a known deadline come last. The entries have to be loaded first (see [load_all])
+pub fn open_submissions(lessons: &[Lesson], now: &DateTime<Utc>) -> Vec<OpenSubmission> {
+    let mut submissions: Vec<OpenSubmission> = Vec::new();
+
+    for lesson in lessons {
+        let entries = lesson
+            .entries
+            .iter()
+            .flatten()
+            .chain(lesson.entry_latest.iter());
+        for entry in entries {
+            for upload in entry.uploads.iter().flatten() {
+                let state = upload.submission_state(now);
+                let open = upload.state || state == SubmissionState::NotYetOpen;
+                if !open || submissions.iter().any(|known| known.upload.id == upload.id) {
+                    continue;
+                }
+
+                submissions.push(OpenSubmission {
+                    lesson_id: lesson.id,
+                    course: lesson.name.to_owned(),
+                    teacher: lesson.teacher.to_owned(),
+                    entry_id: entry.id,
+                    entry_title: entry.title.to_owned(),
+                    deadline: upload.deadline(),
+                    state,
+                    upload: upload.to_owned(),
+                });
+            }
+        }
+    }
+    submissions.sort_by_key(|submission| (submission.deadline.is_none(), submission.deadline));
+
+    submissions
+}
+
+/// Which homework [homework] returns
+#[derive(
+    Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Serialize, Deserialize,
+)]
+pub enum HomeworkFilter {
+    #[default]
+    All,
+    Open,
+    Completed,
+}
+
+/// The homework of one [LessonEntry] together with its course
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct HomeworkItem {
+    pub lesson_id: i32,
+    pub course: String,
+    pub teacher: String,
+    pub entry_id: i32,
+    pub date: DateTime<Utc>,
+    /// The title of the entry
+    pub title: String,
+    pub description: String,
+    pub completed: bool,
+}
+
+/// Collects the homework of all courses (from [Lesson::entries] and [Lesson::entry_latest]) <br>
+/// The result is sorted by the date of the entry (newest first). The entries have to be loaded first (see [load_all])
+pub fn homework(lessons: &[Lesson], filter: HomeworkFilter) -> Vec<HomeworkItem> {
+    let mut items: Vec<HomeworkItem> = Vec::new();
+
+    for lesson in lessons {
+        let entries = lesson
+            .entries
+            .iter()
+            .flatten()
+            .chain(lesson.entry_latest.iter());
+        for entry in entries {
+            let Some(homework) = &entry.homework else {
+                continue;
+            };
+            let included = match filter {
+                HomeworkFilter::All => true,
+                HomeworkFilter::Open => !homework.completed,
+                HomeworkFilter::Completed => homework.completed,
+            };
+            if !included
+                || items
+                    .iter()
+                    .any(|item| item.lesson_id == lesson.id && item.entry_id == entry.id)
+            {
+                continue;
+            }
+
+            items.push(HomeworkItem {
+                lesson_id: lesson.id,
+                course: lesson.name.to_owned(),
+                teacher: lesson.teacher.to_owned(),
+                entry_id: entry.id,
+                date: entry.date,
+                title: entry.title.to_owned(),
+                description: homework.description.to_owned(),
+                completed: homework.completed,
+            });
+        }
+    }
+    items.sort_by_key(|item| std::cmp::Reverse(item.date));
+
+    items
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct HomeworkToggleFailure {
+    pub lesson_id: i32,
+    pub entry_id: i32,
+    pub error: Error,
+}
+
+/// Marks the given homework as completed or open with [Homework::set_homework] <br>
+/// The homework inside of `lessons` is updated as well (in [Lesson::entries] and [Lesson::entry_latest]).
+/// Errors of single items are returned, all other items are still changed
+pub async fn set_homework_all(
+    lessons: &mut [Lesson],
+    items: &[HomeworkItem],
+    completed: bool,
+    client: &Client,
+) -> Vec<HomeworkToggleFailure> {
+    let mut failures = Vec::new();
+
+    for item in items {
+        let failure = |error| HomeworkToggleFailure {
+            lesson_id: item.lesson_id,
+            entry_id: item.entry_id,
+            error,
+        };
+        let Some(lesson) = lessons
+            .iter_mut()
+            .find(|lesson| lesson.id == item.lesson_id)
+        else {
+            failures.push(failure(Error::InvalidInput(format!(
+                "no lesson with id {} found",
+                item.lesson_id
+            ))));
+            continue;
+        };
+
+        let mut homework = lesson
+            .entries
+            .iter_mut()
+            .flatten()
+            .chain(lesson.entry_latest.iter_mut())
+            .filter(|entry| entry.id == item.entry_id)
+            .filter_map(|entry| entry.homework.as_mut())
+            .collect::<Vec<_>>();
+        let Some(first) = homework.first_mut() else {
+            failures.push(failure(Error::InvalidInput(format!(
+                "no homework in entry {} found",
+                item.entry_id
+            ))));
+            continue;
+        };
+
+        match first
+            .set_homework(completed, item.lesson_id, item.entry_id, client)
+            .await
+        {
+            Ok(()) => {
+                for homework in homework.iter_mut() {
+                    homework.completed = completed;
+                }
+            }
+            Err(error) => failures.push(failure(error)),
+        }
+    }
+
+    failures
+}
@@ -437,13 +437,13 @@ impl ConversationOverview {
                         let mut date = date_split.0.to_string();
                         if date_split.0 == "heute" {
                             let new_date =
-                                format!("{}", chrono::Local::now().date_naive().format("%d.%m.%Y"));
+                                format!("{}", now_local().date_naive().format("%d.%m.%Y"));
                             date = new_date
                         }
                         if date_split.0 == "gestern" {
                             let new_date = format!(
                                 "{}",
-                                (chrono::Local::now() - chrono::Duration::days(1))
+                                (now_local() - chrono::Duration::days(1))
                                     .date_naive()
                                     .format("%d.%m.%Y")
                             );
@@ -657,7 +657,7 @@ impl Conversation {
     }
 
     /// Reply to a [Conversation] (send a message) <br>
-    /// `message` supports lanis formatting (see [here](https://support.schulportal.hessen.de/knowledgebase.php?article=664) for more info) <br>
+    /// `message` supports lanis formatting (see [here](https://support.schulportal.hessen.de/knowledgebase.php?article=664) for more info and [crate::utils::markup::LanisMarkupBuilder]) <br>
     /// returns the UID of the new message (None if new message failed)
     pub async fn reply(
         &self,
@@ -747,7 +747,8 @@ pub struct Participant {
 
 #[allow(unused_imports)]
 use crate::base::account::Account;
-use crate::utils::datetime::date_time_string_to_datetime;
+use crate::utils::datetime::{date_time_string_to_datetime, now_local};
+use crate::utils::markup::RichText;
 use crate::Error;
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -763,6 +764,13 @@ pub struct Message {
     pub content: String,
 }
 
+impl Message {
+    /// The content as [RichText] (see [RichText::parse])
+    pub fn rich_content(&self) -> RichText {
+        RichText::parse(&self.content)
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
 pub enum ConversationType {
     /// No answers
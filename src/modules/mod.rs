@@ -1,5 +1,8 @@
+pub mod abbreviations;
 pub mod calendar;
 pub mod file_storage;
 pub mod lessons;
 pub mod messages;
+pub mod outbox;
+pub mod teaching;
 pub mod timetable;
@@ -0,0 +1,446 @@
+use crate::base::account::Account;
+use crate::modules::lessons::{get_lessons, HomeworkItem, Lesson};
+use crate::modules::messages::ConversationOverview;
+use crate::Error;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A small write that can be applied locally first and sent to Lanis later
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum OutboxAction {
+    /// Marks a homework as completed or open (see [crate::modules::lessons::Homework::set_homework])
+    Homework {
+        lesson_id: i32,
+        entry_id: i32,
+        completed: bool,
+        /// The description when the change was made (used to detect changes by the teacher)
+        description: String,
+    },
+    /// Hides or shows a conversation (see [ConversationOverview::hide] and [ConversationOverview::show])
+    ConversationVisibility { uid: String, visible: bool },
+}
+
+impl OutboxAction {
+    /// Returns true if both actions change the same thing
+    fn same_target(&self, other: &OutboxAction) -> bool {
+        match (self, other) {
+            (
+                OutboxAction::Homework {
+                    lesson_id,
+                    entry_id,
+                    ..
+                },
+                OutboxAction::Homework {
+                    lesson_id: other_lesson_id,
+                    entry_id: other_entry_id,
+                    ..
+                },
+            ) => lesson_id == other_lesson_id && entry_id == other_entry_id,
+            (
+                OutboxAction::ConversationVisibility { uid, .. },
+                OutboxAction::ConversationVisibility { uid: other_uid, .. },
+            ) => uid == other_uid,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub id: u64,
+    pub action: OutboxAction,
+    /// The state before the first queued change (e.g. if the homework was completed)
+    pub previous: bool,
+}
+
+/// Why an [OutboxEntry] wasn't sent
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum OutboxConflict {
+    /// The homework or conversation doesn't exist anymore
+    Missing,
+    /// The teacher changed the homework since the change was made (contains the new description)
+    HomeworkChanged(String),
+}
+
+/// What happens to an [OutboxEntry] when the state on the server is known
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum OutboxDecision {
+    /// The change has to be sent
+    Send,
+    /// The server already has the desired state
+    AlreadyApplied,
+    Conflict(OutboxConflict),
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct OutboxFailure {
+    pub entry: OutboxEntry,
+    pub error: Error,
+}
+
+/// The result of [Outbox::sync]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct OutboxSyncReport {
+    /// Entries that were sent successfully
+    pub applied: Vec<OutboxEntry>,
+    /// Entries where the server already had the desired state
+    pub already_applied: Vec<OutboxEntry>,
+    /// Entries that were dropped because the server state changed in the meantime
+    pub conflicts: Vec<(OutboxEntry, OutboxConflict)>,
+    /// Entries that failed (e.g. no network) and stay in the outbox
+    pub failures: Vec<OutboxFailure>,
+}
+
+/// A persistent queue of small idempotent writes <br>
+/// Changes are applied locally with [Outbox::apply_to_lessons] / [Outbox::apply_to_conversations] and sent later with [Outbox::sync]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Serialize, Deserialize)]
+pub struct Outbox {
+    pub entries: Vec<OutboxEntry>,
+    next_id: u64,
+}
+
+impl Outbox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the outbox from a JSON file <br>
+    /// Returns an empty outbox if the file doesn't exist
+    pub async fn load(path: &Path) -> Result<Self, Error> {
+        match tokio::fs::read(path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                Error::Parsing(format!(
+                    "failed to parse outbox '{}' with error '{}'",
+                    path.display(),
+                    e
+                ))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(Error::FileSystem(format!(
+                "failed to read outbox '{}' with error '{}'",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    /// Saves the outbox as JSON <br>
+    /// The file is written next to `path` first and then renamed, so a crash never leaves a broken outbox behind
+    pub async fn save(&self, path: &Path) -> Result<(), Error> {
+        let json = serde_json::to_vec(self)
+            .map_err(|e| Error::Parsing(format!("failed to serialize outbox '{}'", e)))?;
+        let temporary = path.with_extension("tmp");
+
+        tokio::fs::write(&temporary, json).await.map_err(|e| {
+            Error::FileSystem(format!(
+                "failed to write outbox '{}' with error '{}'",
+                temporary.display(),
+                e
+            ))
+        })?;
+        tokio::fs::rename(&temporary, path).await.map_err(|e| {
+            Error::FileSystem(format!(
+                "failed to move outbox to '{}' with error '{}'",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Queues an action <br>
+    /// A queued action for the same homework / conversation is replaced. If the new action restores the original state both are dropped
+    pub fn push(&mut self, action: OutboxAction, previous: bool) {
+        let previous = match self
+            .entries
+            .iter()
+            .position(|entry| entry.action.same_target(&action))
+        {
+            Some(position) => self.entries.remove(position).previous,
+            None => previous,
+        };
+
+        let desired = match &action {
+            OutboxAction::Homework { completed, .. } => *completed,
+            OutboxAction::ConversationVisibility { visible, .. } => *visible,
+        };
+        if desired == previous {
+            return;
+        }
+
+        self.next_id += 1;
+        self.entries.push(OutboxEntry {
+            id: self.next_id,
+            action,
+            previous,
+        });
+    }
+
+    /// Queues marking the homework as completed or open
+    pub fn set_homework(&mut self, item: &HomeworkItem, completed: bool) {
+        self.push(
+            OutboxAction::Homework {
+                lesson_id: item.lesson_id,
+                entry_id: item.entry_id,
+                completed,
+                description: item.description.to_owned(),
+            },
+            item.completed,
+        );
+    }
+
+    /// Queues hiding or showing the conversation
+    pub fn set_conversation_visibility(
+        &mut self,
+        conversation: &ConversationOverview,
+        visible: bool,
+    ) {
+        self.push(
+            OutboxAction::ConversationVisibility {
+                uid: conversation.uid.to_owned(),
+                visible,
+            },
+            conversation.visible,
+        );
+    }
+
+    /// Applies all queued homework changes to the given lessons (e.g. after loading them from Lanis again)
+    pub fn apply_to_lessons(&self, lessons: &mut [Lesson]) {
+        for entry in &self.entries {
+            if let OutboxAction::Homework {
+                lesson_id,
+                entry_id,
+                completed,
+                ..
+            } = &entry.action
+            {
+                let homework = lessons
+                    .iter_mut()
+                    .filter(|lesson| lesson.id == *lesson_id)
+                    .flat_map(|lesson| {
+                        lesson
+                            .entries
+                            .iter_mut()
+                            .flatten()
+                            .chain(lesson.entry_latest.iter_mut())
+                    })
+                    .filter(|lesson_entry| lesson_entry.id == *entry_id)
+                    .filter_map(|lesson_entry| lesson_entry.homework.as_mut());
+                for homework in homework {
+                    homework.completed = *completed;
+                }
+            }
+        }
+    }
+
+    /// Applies all queued visibility changes to the given conversations
+    pub fn apply_to_conversations(&self, conversations: &mut [ConversationOverview]) {
+        for entry in &self.entries {
+            if let OutboxAction::ConversationVisibility { uid, visible } = &entry.action {
+                for conversation in conversations
+                    .iter_mut()
+                    .filter(|conversation| conversation.uid == *uid)
+                {
+                    conversation.visible = *visible;
+                }
+            }
+        }
+    }
+
+    /// Decides what to do with `entry` given the current state on the server
+    pub fn decide(
+        entry: &OutboxEntry,
+        lessons: &[Lesson],
+        conversations: &[ConversationOverview],
+    ) -> OutboxDecision {
+        match &entry.action {
+            OutboxAction::Homework {
+                lesson_id,
+                entry_id,
+                completed,
+                description,
+            } => {
+                let homework = lessons
+                    .iter()
+                    .filter(|lesson| lesson.id == *lesson_id)
+                    .flat_map(|lesson| {
+                        lesson
+                            .entries
+                            .iter()
+                            .flatten()
+                            .chain(lesson.entry_latest.iter())
+                    })
+                    .filter(|lesson_entry| lesson_entry.id == *entry_id)
+                    .find_map(|lesson_entry| lesson_entry.homework.as_ref());
+
+                match homework {
+                    None => OutboxDecision::Conflict(OutboxConflict::Missing),
+                    Some(homework) if homework.description != *description => {
+                        OutboxDecision::Conflict(OutboxConflict::HomeworkChanged(
+                            homework.description.to_owned(),
+                        ))
+                    }
+                    Some(homework) if homework.completed == *completed => {
+                        OutboxDecision::AlreadyApplied
+                    }
+                    Some(_) => OutboxDecision::Send,
+                }
+            }
+            OutboxAction::ConversationVisibility { uid, visible } => {
+                match conversations
+                    .iter()
+                    .find(|conversation| conversation.uid == *uid)
+                {
+                    None => OutboxDecision::Conflict(OutboxConflict::Missing),
+                    Some(conversation) if conversation.visible == *visible => {
+                        OutboxDecision::AlreadyApplied
+                    }
+                    Some(_) => OutboxDecision::Send,
+                }
+            }
+        }
+    }
+
+    /// Sends all queued changes to Lanis <br>
+    /// The current state is loaded first (only the courses and conversations that are needed) to detect conflicts.
+    /// Sent, already applied and conflicting entries are removed from the outbox, failed entries stay queued. <br>
+    /// Remember to [Outbox::save] the outbox afterwards
+    pub async fn sync(&mut self, account: &Account) -> OutboxSyncReport {
+        let mut report = OutboxSyncReport::default();
+        if self.entries.is_empty() {
+            return report;
+        }
+
+        let lesson_ids = self
+            .entries
+            .iter()
+            .filter_map(|entry| match &entry.action {
+                OutboxAction::Homework { lesson_id, .. } => Some(*lesson_id),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let needs_conversations = self
+            .entries
+            .iter()
+            .any(|entry| matches!(entry.action, OutboxAction::ConversationVisibility { .. }));
+
+        // Nothing can be decided without the current state, so every entry stays queued
+        let state = async {
+            let mut lessons = Vec::new();
+            if !lesson_ids.is_empty() {
+                for mut lesson in get_lessons(account).await? {
+                    if lesson_ids.contains(&lesson.id) {
+                        lesson.set_data(account).await?;
+                        lessons.push(lesson);
+                    }
+                }
+            }
+            let conversations = if needs_conversations {
+                ConversationOverview::get_root(&account.client, &account.key_pair).await?
+            } else {
+                Vec::new()
+            };
+
+            Ok::<_, Error>((lessons, conversations))
+        }
+        .await;
+        let (mut lessons, mut conversations) = match state {
+            Ok(state) => state,
+            Err(error) => {
+                report.failures = self
+                    .entries
+                    .iter()
+                    .map(|entry| OutboxFailure {
+                        entry: entry.to_owned(),
+                        error: error.to_owned(),
+                    })
+                    .collect();
+                return report;
+            }
+        };
+
+        let mut remaining = Vec::new();
+        for entry in std::mem::take(&mut self.entries) {
+            match Self::decide(&entry, &lessons, &conversations) {
+                OutboxDecision::AlreadyApplied => report.already_applied.push(entry),
+                OutboxDecision::Conflict(conflict) => report.conflicts.push((entry, conflict)),
+                OutboxDecision::Send => {
+                    match send(&entry.action, &mut lessons, &mut conversations, account).await {
+                        Ok(()) => report.applied.push(entry),
+                        Err(error) => {
+                            remaining.push(entry.to_owned());
+                            report.failures.push(OutboxFailure { entry, error });
+                        }
+                    }
+                }
+            }
+        }
+        self.entries = remaining;
+
+        report
+    }
+}
+
+/// Sends a single action (the server state was checked before)
+async fn send(
+    action: &OutboxAction,
+    lessons: &mut [Lesson],
+    conversations: &mut [ConversationOverview],
+    account: &Account,
+) -> Result<(), Error> {
+    match action {
+        OutboxAction::Homework {
+            lesson_id,
+            entry_id,
+            completed,
+            ..
+        } => {
+            let homework = lessons
+                .iter_mut()
+                .filter(|lesson| lesson.id == *lesson_id)
+                .flat_map(|lesson| {
+                    lesson
+                        .entries
+                        .iter_mut()
+                        .flatten()
+                        .chain(lesson.entry_latest.iter_mut())
+                })
+                .filter(|lesson_entry| lesson_entry.id == *entry_id)
+                .find_map(|lesson_entry| lesson_entry.homework.as_mut())
+                .ok_or(Error::InvalidInput(format!(
+                    "no homework in entry {} found",
+                    entry_id
+                )))?;
+
+            homework
+                .set_homework(*completed, *lesson_id, *entry_id, &account.client)
+                .await
+        }
+        OutboxAction::ConversationVisibility { uid, visible } => {
+            let conversation = conversations
+                .iter_mut()
+                .find(|conversation| conversation.uid == *uid)
+                .ok_or(Error::InvalidInput(format!(
+                    "no conversation with uid {} found",
+                    uid
+                )))?;
+
+            let changed = if *visible {
+                conversation.show(&account.client).await?
+            } else {
+                conversation.hide(&account.client).await?
+            };
+            if changed || conversation.visible == *visible {
+                Ok(())
+            } else {
+                Err(Error::ServerSide(format!(
+                    "failed to change the visibility of conversation {}",
+                    uid
+                )))
+            }
+        }
+    }
+}
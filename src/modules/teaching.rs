@@ -0,0 +1,204 @@
+use crate::base::account::{Account, AccountType};
+use crate::modules::lessons::{
+    LessonUploadInfoOwnFile, MirrorFailure, MirrorProgress, MirrorResult,
+};
+use crate::utils::constants::URL;
+use crate::utils::crypt::decrypt_lanis_encoded_tags;
+use crate::utils::download::{sanitize_file_name, unique_file_name, RemoteFile};
+use crate::Error;
+use regex::Regex;
+use reqwest::Client;
+use scraper::{Html, Selector};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Fails if the account isn't a teacher account (the actions of this module are rejected by Lanis otherwise)
+fn require_teacher(account: &Account) -> Result<(), Error> {
+    match account.account_type {
+        AccountType::Teacher => Ok(()),
+        _ => Err(Error::InvalidInput(format!(
+            "this action needs a teacher account, but the account is a '{}' account",
+            account.account_type
+        ))),
+    }
+}
+
+/// The files of one student in an upload
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct StudentSubmission {
+    /// The name as shown by Lanis (e.g. "Mustermann, Max")
+    pub student: String,
+    /// Empty if the student didn't upload anything yet
+    pub files: Vec<LessonUploadInfoOwnFile>,
+}
+
+impl StudentSubmission {
+    pub fn is_submitted(&self) -> bool {
+        !self.files.is_empty()
+    }
+}
+
+static FILE_INDEX_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"f=(\d+)").unwrap());
+
+/// Parses the submissions table of the teacher view of an upload <br>
+/// The table is the one with a "Dateien" column. Every row is one student, the links to the files contain their index (`f=`)
+pub(crate) fn parse_submissions(document: &Html) -> Result<Vec<StudentSubmission>, Error> {
+    let table_selector = Selector::parse("table").unwrap();
+    let header_selector = Selector::parse("thead th").unwrap();
+    let file_selector = Selector::parse("a[href*='f=']").unwrap();
+
+    let (table, files_column) = document
+        .select(&table_selector)
+        .find_map(|table| {
+            let column = table
+                .select(&header_selector)
+                .position(|header| header.text().collect::<String>().trim() == "Dateien")?;
+            Some((table, column))
+        })
+        .ok_or_else(|| Error::Parsing(String::from("no submissions table found")))?;
+
+    let rows = table
+        .child_elements()
+        .filter(|element| element.value().name() == "tbody")
+        .flat_map(|body| body.child_elements())
+        .filter(|element| element.value().name() == "tr");
+
+    let mut submissions = Vec::new();
+    for row in rows {
+        let cells = row
+            .child_elements()
+            .filter(|element| element.value().name() == "td")
+            .collect::<Vec<_>>();
+        let Some(student) = cells.first() else {
+            continue;
+        };
+        let student = student
+            .text()
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if student.is_empty() {
+            continue;
+        }
+
+        let mut files = Vec::new();
+        for link in cells
+            .get(files_column)
+            .iter()
+            .flat_map(|cell| cell.select(&file_selector))
+        {
+            let href = link.value().attr("href").unwrap_or_default();
+            let index = FILE_INDEX_REGEX
+                .captures(href)
+                .and_then(|captures| captures[1].parse::<i32>().ok())
+                .ok_or_else(|| {
+                    Error::Parsing(format!("failed to parse the index of the file '{}'", href))
+                })?;
+            files.push(LessonUploadInfoOwnFile {
+                name: link.text().collect::<String>().trim().to_string(),
+                url: format!("{}{}", URL::BASE, href),
+                index,
+                comment: link
+                    .value()
+                    .attr("title")
+                    .map(|title| title.trim().to_string()),
+            });
+        }
+
+        submissions.push(StudentSubmission { student, files });
+    }
+
+    Ok(submissions)
+}
+
+/// Gets the files of every student of an upload, including students that didn't submit anything <br>
+/// NOTE: The url of the teacher view of an upload and its layout aren't verified against real Lanis pages yet
+pub async fn get_submissions(
+    course_id: i32,
+    entry_id: i32,
+    upload_id: i32,
+    account: &Account,
+) -> Result<Vec<StudentSubmission>, Error> {
+    require_teacher(account)?;
+    let url = format!(
+        "{}?a=abgabe&b={}&e={}&id={}",
+        URL::MEIN_UNTERRICHT,
+        course_id,
+        entry_id,
+        upload_id
+    );
+
+    let response = account
+        .client
+        .get(&url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| Error::Network(format!("failed to get '{}' with error '{}'", url, e)))?;
+    let response = response
+        .text()
+        .await
+        .map_err(|e| Error::Parsing(format!("failed to parse response with error '{}'", e)))?;
+    let response = decrypt_lanis_encoded_tags(&response, &account.key_pair.public_key_string).await;
+
+    parse_submissions(&Html::parse_document(&response))
+}
+
+/// Returns the path of every submitted file inside of `root` <br>
+/// The structure is `<root>/<student>/<file>`. Students and files with the same name get a number appended (e.g. "Aufgabe (2).pdf")
+pub fn submission_paths(
+    submissions: &[StudentSubmission],
+    root: &Path,
+) -> Vec<(PathBuf, LessonUploadInfoOwnFile)> {
+    let mut paths = Vec::new();
+    let mut folders = BTreeSet::new();
+    for submission in submissions {
+        let folder = root.join(unique_file_name(
+            &sanitize_file_name(&submission.student),
+            &mut folders,
+        ));
+        let mut names = BTreeSet::new();
+        for file in &submission.files {
+            let name = unique_file_name(&sanitize_file_name(&file.name), &mut names);
+            paths.push((folder.join(name), file.to_owned()));
+        }
+    }
+    paths
+}
+
+/// Downloads all submitted files into `root` (see [submission_paths]) <br>
+/// Existing files are only replaced once their download succeeded, errors of single files are returned in [MirrorResult::failures]
+pub async fn download_submissions<F>(
+    submissions: &[StudentSubmission],
+    root: &Path,
+    client: &Client,
+    mut progress: F,
+) -> MirrorResult
+where
+    F: FnMut(MirrorProgress),
+{
+    let paths = submission_paths(submissions, root);
+    let files = paths.len();
+    let mut result = MirrorResult::default();
+
+    for (index, (path, file)) in paths.into_iter().enumerate() {
+        let download = file
+            .download(&path, client, |download| {
+                progress(MirrorProgress {
+                    path: path.to_owned(),
+                    file: index + 1,
+                    files,
+                    download,
+                })
+            })
+            .await;
+        match download {
+            Ok(_) => result.downloaded.push(path),
+            Err(error) => result.failures.push(MirrorFailure { path, error }),
+        }
+    }
+
+    result
+}
@@ -1,19 +1,21 @@
-use crate::base::account::UntisSecrets;
+use crate::base::account::{Account, UntisSecrets};
+use crate::base::untis::{rpc_error, UntisSession};
 use crate::utils::constants::URL;
-use crate::utils::datetime::merge_naive_date_time_to_datetime;
+use crate::utils::datetime::{datetime_to_local, merge_naive_date_time_to_datetime, now_local};
 use crate::Error;
-use chrono::{DateTime, Days, NaiveDate, NaiveTime, Utc};
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, Utc};
+use futures::{stream, StreamExt, TryStreamExt};
 use reqwest::Client;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::fmt::Debug;
-use untis::LessonCode;
+use untis::{ElementType, LessonCode};
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
 pub enum Provider {
     Lanis(LanisType),
-    Untis(UntisSecrets),
+    Untis(UntisSecrets, UntisType),
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
@@ -22,11 +24,177 @@ pub enum LanisType {
     Own,
 }
 
+/// Which timetable should be fetched from Untis
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum UntisType {
+    /// The personal timetable of the logged in user
+    Own,
+    /// The timetable of the class of the logged in user
+    OwnClass,
+    Class(UntisElement),
+    Teacher(UntisElement),
+    Room(UntisElement),
+    Subject(UntisElement),
+}
+
+/// Identifies a class, teacher, room or subject in Untis
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum UntisElement {
+    /// The id from the master data (see [UntisSession::classes] etc.)
+    Id(usize),
+    /// The short or long name (e.g. "5a", "MUE" or "B209") <br>
+    /// Names are compared case-insensitive and need an extra request to resolve the id
+    Name(String),
+}
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct Week {
     pub week: NaiveDate,
     pub week_type: Option<char>,
+    /// The monday of the week that has [Week::week_type] <br>
+    /// Lanis only shows the type of the current week, so for [Provider::Lanis] this is the week in which the plan was fetched
+    pub week_type_monday: Option<NaiveDate>,
     pub entries: Vec<LessonEntry>,
+    /// Holidays that overlap with this week <br>
+    /// Only available if [Provider::Untis] is used
+    pub holidays: Vec<Holiday>,
+    /// Exams in this week <br>
+    /// Only available if [Provider::Untis] is used and the school allows access to exams
+    pub exams: Vec<Exam>,
+    /// The times of the school hours (also available if there are no lessons)
+    pub school_hour_grid: SchoolHourGrid,
+}
+
+/// The school hours (Stundenraster) of a school
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct SchoolHourGrid {
+    /// Sorted by start time
+    pub hours: Vec<SchoolHour>,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct SchoolHour {
+    /// The number of the school hour (e.g. 3 for the third hour)
+    pub number: i32,
+    /// Local time
+    pub start: NaiveTime,
+    /// Local time
+    pub end: NaiveTime,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct SchoolBreak {
+    /// The number of the school hour before the break
+    pub after: i32,
+    /// Local time
+    pub start: NaiveTime,
+    /// Local time
+    pub end: NaiveTime,
+}
+
+impl SchoolHourGrid {
+    pub fn new(mut hours: Vec<SchoolHour>) -> Self {
+        hours.sort_by_key(|hour| (hour.start, hour.number));
+        Self { hours }
+    }
+
+    /// Gets the grid from Untis using an already logged in [UntisSession] <br>
+    /// Untis can have a different grid per day, the grid of the day with the most school hours is used
+    pub async fn new_untis(session: &UntisSession) -> Result<Self, Error> {
+        let timegrid = session
+            .rpc_cached::<Vec<UntisTimegridDay>>("getTimegridUnits")
+            .await?
+            .map_err(|e| rpc_error("getTimegridUnits", e))?;
+
+        Ok(Self::from_untis(&timegrid))
+    }
+
+    fn from_untis(timegrid: &[UntisTimegridDay]) -> Self {
+        match timegrid.iter().max_by_key(|day| day.time_units.len()) {
+            Some(day) => Self::new(
+                day.time_units
+                    .iter()
+                    .enumerate()
+                    .map(|(i, unit)| SchoolHour {
+                        number: unit.number(i),
+                        start: *unit.start_time,
+                        end: *unit.end_time,
+                    })
+                    .collect(),
+            ),
+            None => Self::default(),
+        }
+    }
+
+    /// Returns the school hour with the given number
+    pub fn hour(&self, number: i32) -> Option<&SchoolHour> {
+        self.hours.iter().find(|hour| hour.number == number)
+    }
+
+    /// Returns the school hour that takes place at `time` (local time) <br>
+    /// Returns [None] during breaks and outside of school hours
+    pub fn hour_at(&self, time: &NaiveTime) -> Option<&SchoolHour> {
+        self.hours
+            .iter()
+            .find(|hour| hour.start <= *time && *time < hour.end)
+    }
+
+    /// Returns the next school hour that starts after `time` (local time)
+    pub fn next_hour(&self, time: &NaiveTime) -> Option<&SchoolHour> {
+        self.hours.iter().find(|hour| hour.start > *time)
+    }
+
+    /// Checks if the school hour `second` directly follows `first` (a break in between is allowed) <br>
+    /// Without a grid the numbers are compared
+    pub fn are_consecutive(&self, first: i32, second: i32) -> bool {
+        let position = |number: i32| self.hours.iter().position(|hour| hour.number == number);
+        match (position(first), position(second)) {
+            (Some(first), Some(second)) => first + 1 == second,
+            _ => first + 1 == second,
+        }
+    }
+
+    /// Returns all gaps between two consecutive school hours
+    pub fn breaks(&self) -> Vec<SchoolBreak> {
+        self.hours
+            .windows(2)
+            .filter(|hours| hours[0].end < hours[1].start)
+            .map(|hours| SchoolBreak {
+                after: hours[0].number,
+                start: hours[0].end,
+                end: hours[1].start,
+            })
+            .collect()
+    }
+
+    /// Returns the break that takes place at `time` (local time)
+    pub fn break_at(&self, time: &NaiveTime) -> Option<SchoolBreak> {
+        self.breaks()
+            .into_iter()
+            .find(|school_break| school_break.start <= *time && *time < school_break.end)
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Holiday {
+    pub name: String,
+    pub long_name: String,
+    /// The first day of the holiday
+    pub start: NaiveDate,
+    /// The last day of the holiday (inclusive)
+    pub end: NaiveDate,
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Exam {
+    /// The name of the exam type (e.g. "Klassenarbeit")
+    pub exam_type: String,
+    pub subjects: Vec<String>,
+    pub teachers: Vec<String>,
+    /// The names of the classes that write this exam
+    pub classes: Vec<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -35,7 +203,10 @@ pub struct LessonEntry {
     /// The names of the Subjects
     pub subjects: Vec<String>,
     pub teachers: Vec<String>,
-    /// School hours are **only** available if [Provider::Lanis] is used
+    /// The names of the classes (e.g. 5a) <br>
+    /// Only available if [Provider::Untis] is used
+    pub classes: Vec<String>,
+    /// May be empty if [Provider::Untis] is used and the lesson doesn't match the timegrid of the school
     pub school_hours: Vec<i32>,
     pub start: DateTime<Utc>,
     pub end: DateTime<Utc>,
@@ -45,9 +216,19 @@ pub struct LessonEntry {
     pub lesson_text: Option<String>,
     /// Only available if [Provider::Untis] is used
     pub substitution_text: Option<String>,
+    /// The full names of the teachers <br>
+    /// Only available after [crate::modules::abbreviations::Abbreviations::resolve_week]
+    pub teacher_names: Vec<String>,
+    /// The course names of the subjects <br>
+    /// Only available after [crate::modules::abbreviations::Abbreviations::resolve_week]
+    pub subject_names: Vec<String>,
+    /// The week type (e.g. 'A') if the lesson only takes place every other week <br>
+    /// Only available if [Provider::Lanis] is used
+    pub week_type: Option<char>,
 }
 
 impl LessonEntry {
+    /// Creates an entry without classes, names and week type (set the fields afterwards if they are known, e.g. `LessonEntry { classes, ..LessonEntry::new(..) }`)
     pub fn new(
         status: LessonEntryStatus,
         subjects: Vec<String>,
@@ -63,14 +244,108 @@ impl LessonEntry {
             status,
             subjects,
             teachers,
+            classes: Vec::new(),
             school_hours,
             start,
             end,
             rooms,
             lesson_text,
             substitution_text,
+            teacher_names: Vec::new(),
+            subject_names: Vec::new(),
+            week_type: None,
+        }
+    }
+
+    /// Checks if this lesson takes place in a week of the given type <br>
+    /// Lessons without a week type take place every week, and every lesson takes place if the type of the week is unknown
+    pub fn in_week_type(&self, week_type: Option<char>) -> bool {
+        match (self.week_type, week_type) {
+            (Some(entry_type), Some(week_type)) => entry_type == week_type,
+            _ => true,
+        }
+    }
+
+    /// Checks if this lesson takes place on the weekday of `date` during `slot` <br>
+    /// Cancelled lessons never take place. A/B weeks aren't checked here, use [Week::lessons_at] for that
+    pub fn takes_place(&self, date: &NaiveDate, slot: &TimeSlot) -> bool {
+        if self.status == LessonEntryStatus::Cancelled {
+            return false;
+        }
+
+        let start = datetime_to_local(&self.start);
+        let end = datetime_to_local(&self.end);
+        if start.weekday() != date.weekday() {
+            return false;
+        }
+
+        match slot {
+            TimeSlot::SchoolHour(hour) => self.school_hours.contains(hour),
+            TimeSlot::Time(from, to) => start.time() < *to && end.time() > *from,
         }
     }
+
+    /// Checks if the given [Participant] takes part in this lesson (names are compared case-insensitive)
+    pub fn has_participant(&self, participant: &Participant) -> bool {
+        match participant {
+            Participant::Teacher(name) => self
+                .teachers
+                .iter()
+                .any(|teacher| matches_name(teacher, name)),
+            // Lanis doesn't list the classes of a lesson, but the class is usually part of the course name (e.g. "05a D")
+            Participant::Class(name) => {
+                let name = normalize_class(name);
+                self.classes
+                    .iter()
+                    .chain(self.subjects.iter())
+                    .any(|class| {
+                        class
+                            .split(|c: char| !c.is_alphanumeric())
+                            .any(|word| normalize_class(word) == name)
+                    })
+            }
+        }
+    }
+}
+
+/// Checks if `value` is `name` or contains `name` as a separate word (e.g. "Müller (MUE)" and "MUE")
+fn matches_name(value: &str, name: &str) -> bool {
+    let name = name.trim();
+    !name.is_empty()
+        && (value.trim().eq_ignore_ascii_case(name)
+            || value
+                .split(|c: char| !c.is_alphanumeric())
+                .any(|word| word.to_lowercase() == name.to_lowercase()))
+}
+
+/// Lowercases a class name and removes leading zeros, so "05a" and "5A" are the same class
+fn normalize_class(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    let trimmed = name.trim_start_matches('0');
+    if trimmed.starts_with(|c: char| c.is_ascii_digit()) {
+        trimmed.to_string()
+    } else {
+        name
+    }
+}
+
+/// A school hour or a time range on a day of a [Week]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum TimeSlot {
+    /// A school hour (e.g. 3 for the third hour)
+    SchoolHour(i32),
+    /// A time range (local time) <br>
+    /// Every lesson that overlaps with it is included
+    Time(NaiveTime, NaiveTime),
+}
+
+/// A teacher or class that can be searched with [Week::where_is]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum Participant {
+    /// The short name or full name of a teacher (e.g. "MUE")
+    Teacher(String),
+    /// The name of a class (e.g. "05a", leading zeros are ignored so "5a" works as well)
+    Class(String),
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
@@ -81,19 +356,25 @@ pub enum LessonEntryStatus {
 }
 
 impl Week {
-    pub async fn new(provider: Provider, client: &Client, date: NaiveDate) -> Result<Week, Error> {
+    /// Gets the timetable of the week of `date` <br>
+    /// Untis sessions are stored in the account (see [Account::get_untis_session_for]), so only the first Untis week logs in
+    pub async fn new(
+        provider: Provider,
+        account: &mut Account,
+        date: NaiveDate,
+    ) -> Result<Week, Error> {
         return match provider {
             Provider::Lanis(LanisType::All) => {
-                let result = lanis(LanisType::All, client).await?;
+                let result = lanis(LanisType::All, &account.client).await?;
                 Ok(result)
             }
             Provider::Lanis(LanisType::Own) => {
-                let result = lanis(LanisType::Own, client).await?;
+                let result = lanis(LanisType::Own, &account.client).await?;
                 Ok(result)
             }
-            // TODO: Implement Untis support
-            Provider::Untis(secrets) => {
-                let result = untis(secrets, date).await?;
+            Provider::Untis(secrets, untis_type) => {
+                let session = account.get_untis_session_for(&secrets).await?;
+                let result = Week::new_untis(&session, untis_type, date).await?;
                 Ok(result)
             }
         };
@@ -141,6 +422,7 @@ impl Week {
                 *week = date;
 
                 let lesson_selector = Selector::parse("div.stunde ").unwrap();
+                let week_type_badge_selector = Selector::parse(".badge").unwrap();
                 let school_hour_time_selector =
                     Selector::parse("span.hidden-xs>span.VonBis>small").unwrap();
 
@@ -234,6 +516,17 @@ impl Week {
                                 .replace("\n", "")
                                 .trim()
                                 .to_string()];
+                            let lesson_week_type = lesson
+                                .select(&week_type_badge_selector)
+                                .next()
+                                .and_then(|badge| {
+                                    let text = badge.text().collect::<String>();
+                                    let mut chars = text.trim().chars();
+                                    match (chars.next(), chars.next()) {
+                                        (Some(week_type), None) => Some(week_type),
+                                        _ => None,
+                                    }
+                                });
                             let mut teachers = Vec::new();
                             for teacher in lesson.text().nth(3).unwrap().split("\n") {
                                 if !teacher.trim().is_empty() {
@@ -286,12 +579,16 @@ impl Week {
                                 status: LessonEntryStatus::Normal,
                                 subjects,
                                 teachers,
+                                classes: Vec::new(),
                                 school_hours,
                                 start,
                                 end,
                                 rooms,
                                 lesson_text: None,
                                 substitution_text: None,
+                                teacher_names: Vec::new(),
+                                subject_names: Vec::new(),
+                                week_type: lesson_week_type,
                             });
                         }
                     }
@@ -314,10 +611,25 @@ impl Week {
                     }
                 };
 
+                let today = now_local().date_naive();
                 let week = Week {
                     week: week.to_owned(),
                     week_type,
+                    week_type_monday: week_type
+                        .map(|_| today - Days::new(today.weekday().num_days_from_monday() as u64)),
                     entries,
+                    holidays: Vec::new(),
+                    exams: Vec::new(),
+                    school_hour_grid: SchoolHourGrid::new(
+                        hour_times
+                            .iter()
+                            .map(|(number, [start, end])| SchoolHour {
+                                number: *number as i32,
+                                start: *start,
+                                end: *end,
+                            })
+                            .collect(),
+                    ),
                 };
                 Ok(week)
             }
@@ -388,75 +700,187 @@ impl Week {
             }
             Ok(result)
         }
+    }
 
-        async fn untis(untis_secrets: UntisSecrets, week: NaiveDate) -> Result<Week, Error> {
-            let school = tokio::task::spawn_blocking(move || {
-                untis::schools::get_by_name(untis_secrets.school_name.as_str())
-                    .map_err(|e| Error::Credentials(format!("failed to get school: '{}'", e)))
-            })
-            .await
-            .map_err(|e| Error::Threading(format!("Failed to join handle: '{}'", e)))??;
+    /// Gets all weeks between `from` and `to` (inclusive), [Week::week] is the monday of each week <br>
+    /// Lanis only provides the current plan, so it is fetched once and put onto every week (only lessons of the matching A/B week are kept). <br>
+    /// Untis weeks are fetched with the session of the account and at most `concurrency` requests at the same time
+    /// (the session itself allows up to [crate::base::untis::UNTIS_MAX_PARALLEL_REQUESTS])
+    pub async fn range(
+        provider: Provider,
+        account: &mut Account,
+        from: NaiveDate,
+        to: NaiveDate,
+        concurrency: usize,
+    ) -> Result<Vec<Week>, Error> {
+        let mut mondays = Vec::new();
+        let mut monday = from - Days::new(from.weekday().num_days_from_monday() as u64);
+        while monday <= to {
+            mondays.push(monday);
+            monday = monday + Days::new(7);
+        }
 
-            let mut client = tokio::task::spawn_blocking(move || {
-                school
-                    .client_login(&untis_secrets.username, &untis_secrets.password)
-                    .map_err(|e| Error::Credentials(format!("failed to login: '{}'", e)))
-            })
-            .await
-            .map_err(|e| Error::Threading(format!("Failed to join handle: '{}'", e)))??;
+        match provider {
+            Provider::Lanis(lanis_type) => {
+                let plan = Week::new(Provider::Lanis(lanis_type), account, from).await?;
+                mondays
+                    .into_iter()
+                    .map(|monday| plan.expand_lanis(monday))
+                    .collect()
+            }
+            Provider::Untis(secrets, untis_type) => {
+                let session = account.get_untis_session_for(&secrets).await?;
+                stream::iter(mondays)
+                    .map(|monday| Week::new_untis(&session, untis_type.to_owned(), monday))
+                    .buffered(concurrency.max(1))
+                    .try_collect()
+                    .await
+            }
+        }
+    }
 
-            let timetable = tokio::task::spawn_blocking(move || {
-                client
-                    .own_timetable_for_week(&week.into())
-                    .map_err(|e| Error::UntisAPI(format!("failed to get timetable: '{}'", e)))
-            })
-            .await
-            .map_err(|e| Error::Threading(format!("Failed to join handle: '{}'", e)))??;
+    /// Returns the week type (e.g. 'A') of the week of `date` <br>
+    /// The types alternate every week starting at [Week::week_type_monday]. None if the plan has no week types
+    pub fn week_type_of(&self, date: &NaiveDate) -> Option<char> {
+        let current_type = self.week_type?;
+        let reference = self.week_type_monday?;
+        let monday = *date - Days::new(date.weekday().num_days_from_monday() as u64);
+        if (monday - reference).num_weeks().rem_euclid(2) == 0 {
+            Some(current_type)
+        } else {
+            Some(
+                self.entries
+                    .iter()
+                    .filter_map(|entry| entry.week_type)
+                    .find(|week_type| *week_type != current_type)
+                    // Lessons of only one type are possible, Lanis uses A and B weeks
+                    .unwrap_or(match current_type {
+                        'A' => 'B',
+                        'B' => 'A',
+                        other => other,
+                    }),
+            )
+        }
+    }
 
-            let mut entries = Vec::new();
+    /// Puts the lessons of a Lanis plan onto the week starting at `monday`
+    pub(crate) fn expand_lanis(&self, monday: NaiveDate) -> Result<Week, Error> {
+        let week_type = self.week_type_of(&monday);
 
-            for lesson in timetable {
-                let status = match lesson.code {
-                    LessonCode::Regular => LessonEntryStatus::Normal,
-                    LessonCode::Irregular => LessonEntryStatus::Abnormal,
-                    LessonCode::Cancelled => LessonEntryStatus::Cancelled,
-                };
+        let mut entries = Vec::new();
+        for entry in &self.entries {
+            if entry.week_type.is_some() && week_type.is_some() && entry.week_type != week_type {
+                continue;
+            }
 
-                let subjects = lesson
-                    .subjects
-                    .iter()
-                    .map(|id| id.name.clone())
-                    .collect::<Vec<_>>();
-                let teachers = lesson
-                    .teachers
-                    .iter()
-                    .map(|id| id.name.clone())
-                    .collect::<Vec<_>>();
-                let school_hours = Vec::new();
-                let date = lesson.date.to_chrono();
-                let start = merge_naive_date_time_to_datetime(&date, &lesson.start_time)
+            // Shifting the local time keeps the lessons at the same time if the DST changes in between
+            let shift = |date_time: &DateTime<Utc>| -> Result<DateTime<Utc>, Error> {
+                let local = datetime_to_local(date_time);
+                let date = monday + Days::new(local.weekday().num_days_from_monday() as u64);
+                Ok(merge_naive_date_time_to_datetime(&date, &local.time())
                     .map_err(|e| {
-                        Error::DateTime(format!("Failed to convert start time of lesson: {:?}", e))
+                        Error::DateTime(format!("failed to move lesson to week '{:?}'", e))
                     })?
-                    .to_utc();
-                let end = merge_naive_date_time_to_datetime(&date, &lesson.end_time)
-                    .map_err(|e| {
-                        Error::DateTime(format!("Failed to convert end time of lesson: {:?}", e))
-                    })?
-                    .to_utc();
-                let rooms = lesson
-                    .rooms
-                    .iter()
-                    .map(|id| id.name.clone())
-                    .collect::<Vec<_>>();
-                let lesson_text = if lesson.lstext.is_empty() {
-                    None
-                } else {
-                    Some(lesson.lstext)
-                };
-                let substitution_text = lesson.subst_text;
+                    .to_utc())
+            };
+
+            let mut entry = entry.to_owned();
+            entry.start = shift(&entry.start)?;
+            entry.end = shift(&entry.end)?;
+            entries.push(entry);
+        }
 
-                entries.push(LessonEntry::new(
+        Ok(Week {
+            week: monday,
+            week_type,
+            week_type_monday: week_type.map(|_| monday),
+            entries,
+            holidays: self.holidays.to_owned(),
+            exams: self.exams.to_owned(),
+            school_hour_grid: self.school_hour_grid.to_owned(),
+        })
+    }
+
+    /// Gets the [Week] from Untis using an already logged in [UntisSession] <br>
+    /// Use this instead of [Week::new] with [Provider::Untis] if you need more than one week
+    pub async fn new_untis(
+        session: &UntisSession,
+        untis_type: UntisType,
+        week: NaiveDate,
+    ) -> Result<Week, Error> {
+        let timetable = match untis_type.resolve(session).await? {
+            Some((id, element_type)) => {
+                session
+                    .request(move |client| {
+                        client.timetable_for_week(&id, &element_type, &week.into())
+                    })
+                    .await?
+            }
+            None => {
+                session
+                    .request(move |client| client.own_timetable_for_week(&week.into()))
+                    .await?
+            }
+        };
+
+        // The timegrid, holidays and exams are optional, some schools don't allow students to access them
+        let timegrid = session
+            .rpc_cached::<Vec<UntisTimegridDay>>("getTimegridUnits")
+            .await?
+            .unwrap_or_default();
+
+        let mut entries = Vec::new();
+
+        for lesson in timetable {
+            let status = match lesson.code {
+                LessonCode::Regular => LessonEntryStatus::Normal,
+                LessonCode::Irregular => LessonEntryStatus::Abnormal,
+                LessonCode::Cancelled => LessonEntryStatus::Cancelled,
+            };
+
+            let subjects = lesson
+                .subjects
+                .iter()
+                .map(|id| id.name.clone())
+                .collect::<Vec<_>>();
+            let teachers = lesson
+                .teachers
+                .iter()
+                .map(|id| id.name.clone())
+                .collect::<Vec<_>>();
+            let classes = lesson
+                .classes
+                .iter()
+                .map(|id| id.name.clone())
+                .collect::<Vec<_>>();
+            let date = lesson.date.to_chrono();
+            let school_hours =
+                untis_school_hours(&timegrid, &date, &lesson.start_time, &lesson.end_time);
+            let start = merge_naive_date_time_to_datetime(&date, &lesson.start_time)
+                .map_err(|e| {
+                    Error::DateTime(format!("Failed to convert start time of lesson: {:?}", e))
+                })?
+                .to_utc();
+            let end = merge_naive_date_time_to_datetime(&date, &lesson.end_time)
+                .map_err(|e| {
+                    Error::DateTime(format!("Failed to convert end time of lesson: {:?}", e))
+                })?
+                .to_utc();
+            let rooms = lesson
+                .rooms
+                .iter()
+                .map(|id| id.name.clone())
+                .collect::<Vec<_>>();
+            let lesson_text = if lesson.lstext.is_empty() {
+                None
+            } else {
+                Some(lesson.lstext)
+            };
+            let substitution_text = lesson.subst_text;
+
+            entries.push(LessonEntry {
+                classes,
+                ..LessonEntry::new(
                     status,
                     subjects,
                     teachers,
@@ -466,15 +890,742 @@ impl Week {
                     rooms,
                     lesson_text,
                     substitution_text,
-                ));
+                )
+            });
+        }
+
+        let monday = week - Days::new(week.weekday().num_days_from_monday() as u64);
+        let sunday = monday + Days::new(6);
+
+        let holidays = session
+            .rpc_cached::<Vec<untis::Holiday>>("getHolidays")
+            .await?
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|holiday| *holiday.start_date <= sunday && *holiday.end_date >= monday)
+            .map(|holiday| Holiday {
+                name: holiday.name,
+                long_name: holiday.long_name,
+                start: holiday.start_date.to_chrono(),
+                end: holiday.end_date.to_chrono(),
+            })
+            .collect();
+
+        let exams = untis_exams(session, monday, sunday).await?;
+
+        Ok(Week {
+            week,
+            week_type: None,
+            week_type_monday: None,
+            entries,
+            holidays,
+            exams,
+            school_hour_grid: SchoolHourGrid::from_untis(&timegrid),
+        })
+    }
+
+    /// Returns the lessons that take place on the weekday of `date` sorted by start time <br>
+    /// Cancelled lessons and lessons of the other A/B week are left out. Entries of the same lesson in consecutive school hours (double lessons) are merged into one entry, even if there is a break in between <br>
+    /// Only the weekday of `date` is used because Lanis timetables repeat every week
+    pub fn day(&self, date: &NaiveDate) -> Vec<LessonEntry> {
+        let week_type = self.week_type_of(date);
+        let mut entries = self
+            .entries
+            .iter()
+            .filter(|entry| entry.status != LessonEntryStatus::Cancelled)
+            .filter(|entry| entry.in_week_type(week_type))
+            .filter(|entry| datetime_to_local(&entry.start).weekday() == date.weekday())
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|entry| (entry.start, entry.end));
+
+        let mut day: Vec<LessonEntry> = Vec::new();
+        for entry in entries {
+            let previous = day.iter_mut().find(|previous| {
+                let consecutive = previous.end == entry.start
+                    || match (
+                        previous.school_hours.iter().max(),
+                        entry.school_hours.iter().min(),
+                    ) {
+                        (Some(last), Some(first)) => {
+                            previous.end <= entry.start
+                                && self.school_hour_grid.are_consecutive(*last, *first)
+                        }
+                        _ => false,
+                    };
+                consecutive
+                    && previous.subjects == entry.subjects
+                    && previous.teachers == entry.teachers
+                    && previous.rooms == entry.rooms
+            });
+            match previous {
+                Some(previous) => {
+                    previous.end = entry.end;
+                    previous.school_hours.extend(entry.school_hours.iter());
+                    previous.school_hours.dedup();
+                }
+                None => day.push(entry.to_owned()),
             }
+        }
 
-            Ok(Week {
-                week,
-                week_type: None,
-                entries,
+        day
+    }
+
+    /// Returns the lessons that take place at `now` <br>
+    /// Usually this is one lesson, but there can be more if lessons take place at the same time (e.g. with [LanisType::All])
+    pub fn current_lessons(&self, now: &DateTime<Utc>) -> Vec<LessonEntry> {
+        let now = datetime_to_local(now);
+        self.day(&now.date_naive())
+            .into_iter()
+            .filter(|entry| {
+                datetime_to_local(&entry.start).time() <= now.time()
+                    && now.time() < datetime_to_local(&entry.end).time()
             })
+            .collect()
+    }
+
+    /// Returns the lessons that start next on the same day after `now` <br>
+    /// The rest of a running double lesson doesn't count as next lesson
+    pub fn next_lessons(&self, now: &DateTime<Utc>) -> Vec<LessonEntry> {
+        let now = datetime_to_local(now);
+        let upcoming = self
+            .day(&now.date_naive())
+            .into_iter()
+            .filter(|entry| datetime_to_local(&entry.start).time() > now.time())
+            .collect::<Vec<_>>();
+
+        match upcoming.first() {
+            Some(first) => {
+                let start = datetime_to_local(&first.start).time();
+                upcoming
+                    .into_iter()
+                    .filter(|entry| datetime_to_local(&entry.start).time() == start)
+                    .collect()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the lessons of the day that haven't ended at `now` (including the current lessons)
+    pub fn remaining_lessons(&self, now: &DateTime<Utc>) -> Vec<LessonEntry> {
+        let now = datetime_to_local(now);
+        self.day(&now.date_naive())
+            .into_iter()
+            .filter(|entry| datetime_to_local(&entry.end).time() > now.time())
+            .collect()
+    }
+
+    /// Returns the first lesson that takes place on the weekday of `date`
+    pub fn first_lesson(&self, date: &NaiveDate) -> Option<LessonEntry> {
+        self.day(date).into_iter().next()
+    }
+
+    /// Returns the lesson that ends last on the weekday of `date` (its end is the end of the school day)
+    pub fn last_lesson(&self, date: &NaiveDate) -> Option<LessonEntry> {
+        self.day(date).into_iter().max_by_key(|entry| entry.end)
+    }
+
+    /// Returns all rooms that are used by at least one lesson of this week (sorted)
+    pub fn rooms(&self) -> Vec<String> {
+        let mut rooms = self
+            .entries
+            .iter()
+            .flat_map(|entry| entry.rooms.iter())
+            .filter(|room| !room.trim().is_empty())
+            .cloned()
+            .collect::<Vec<_>>();
+        rooms.sort();
+        rooms.dedup();
+        rooms
+    }
+
+    /// Returns all lessons that take place on the weekday of `date` during `slot` <br>
+    /// Only the weekday of `date` is used because Lanis timetables repeat every week, lessons of the other A/B week are left out (see [Week::week_type_of])
+    pub fn lessons_at(&self, date: &NaiveDate, slot: &TimeSlot) -> Vec<&LessonEntry> {
+        let week_type = self.week_type_of(date);
+        self.entries
+            .iter()
+            .filter(|entry| entry.in_week_type(week_type) && entry.takes_place(date, slot))
+            .collect()
+    }
+
+    /// Returns the rooms that aren't occupied by any lesson on the weekday of `date` during `slot` <br>
+    /// Only rooms that are used somewhere in this week are known, so this should be used with [LanisType::All]
+    pub fn free_rooms(&self, date: &NaiveDate, slot: &TimeSlot) -> Vec<String> {
+        let occupied = self
+            .lessons_at(date, slot)
+            .into_iter()
+            .flat_map(|entry| entry.rooms.iter())
+            .collect::<Vec<_>>();
+
+        self.rooms()
+            .into_iter()
+            .filter(|room| !occupied.contains(&room))
+            .collect()
+    }
+
+    /// Returns the lessons the given teacher or class has on the weekday of `date` during `slot` <br>
+    /// The rooms of the returned lessons tell where the teacher or class is
+    pub fn where_is(
+        &self,
+        participant: &Participant,
+        date: &NaiveDate,
+        slot: &TimeSlot,
+    ) -> Vec<&LessonEntry> {
+        self.lessons_at(date, slot)
+            .into_iter()
+            .filter(|entry| entry.has_participant(participant))
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct UntisTimegridDay {
+    /// 1 = Sunday, 2 = Monday, ..., 7 = Saturday
+    day: u32,
+    #[serde(rename = "timeUnits")]
+    time_units: Vec<UntisTimegridUnit>,
+}
+
+#[derive(Deserialize)]
+struct UntisTimegridUnit {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "startTime")]
+    start_time: untis::Time,
+    #[serde(rename = "endTime")]
+    end_time: untis::Time,
+}
+
+impl UntisTimegridUnit {
+    /// Untis names the units freely, so the position in the day is used if the name isn't a number
+    fn number(&self, index: usize) -> i32 {
+        self.name.trim().parse::<i32>().unwrap_or(index as i32 + 1)
+    }
+}
+
+/// Maps the time of a lesson onto the school hours of the Untis timegrid
+fn untis_school_hours(
+    timegrid: &[UntisTimegridDay],
+    date: &NaiveDate,
+    start: &NaiveTime,
+    end: &NaiveTime,
+) -> Vec<i32> {
+    let day = date.weekday().number_from_sunday();
+    match timegrid.iter().find(|timegrid_day| timegrid_day.day == day) {
+        Some(timegrid_day) => timegrid_day
+            .time_units
+            .iter()
+            .enumerate()
+            .filter(|(_, unit)| *unit.start_time < *end && *unit.end_time > *start)
+            .map(|(i, unit)| unit.number(i))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Gets all exams between `from` and `to` (inclusive) <br>
+/// Returns an empty [Vec] if the school doesn't allow access to exams
+async fn untis_exams(
+    session: &UntisSession,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<Exam>, Error> {
+    #[derive(Deserialize)]
+    struct JsonExamType {
+        id: usize,
+        #[serde(default)]
+        name: String,
+        #[serde(rename = "longName", default)]
+        long_name: String,
+    }
+
+    #[derive(Deserialize)]
+    struct JsonExam {
+        #[serde(default)]
+        classes: Vec<usize>,
+        #[serde(default)]
+        teachers: Vec<usize>,
+        subject: Option<usize>,
+        date: untis::Date,
+        #[serde(rename = "startTime")]
+        start_time: untis::Time,
+        #[serde(rename = "endTime")]
+        end_time: untis::Time,
+    }
+
+    #[derive(Serialize)]
+    struct JsonExamParams {
+        #[serde(rename = "examTypeId")]
+        exam_type_id: usize,
+        #[serde(rename = "startDate")]
+        start_date: untis::Date,
+        #[serde(rename = "endDate")]
+        end_date: untis::Date,
+    }
+
+    let exam_types = match session
+        .rpc_cached::<Vec<JsonExamType>>("getExamTypes")
+        .await?
+    {
+        Ok(exam_types) => exam_types,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    // Names are resolved on a best effort basis because students often can't access all master data
+    fn names<T>(
+        ids: &[usize],
+        items: &Option<Vec<T>>,
+        id: impl Fn(&T) -> usize,
+        name: impl Fn(&T) -> String,
+    ) -> Vec<String> {
+        ids.iter()
+            .map(|i| {
+                items
+                    .iter()
+                    .flatten()
+                    .find(|item| id(item) == *i)
+                    .map(&name)
+                    .unwrap_or(i.to_string())
+            })
+            .collect()
+    }
+    let subjects = session
+        .rpc_cached::<Vec<untis::Subject>>("getSubjects")
+        .await
+        .ok()
+        .and_then(|result| result.ok());
+    let teachers = session
+        .rpc_cached::<Vec<untis::Teacher>>("getTeachers")
+        .await
+        .ok()
+        .and_then(|result| result.ok());
+    let classes = session
+        .rpc_cached::<Vec<untis::Class>>("getKlassen")
+        .await
+        .ok()
+        .and_then(|result| result.ok());
+
+    // Exams are requested per school year (August to July) and cached, so the weeks of one session share the requests
+    let school_year = |date: NaiveDate| {
+        let year = if date.month() >= 8 {
+            date.year()
+        } else {
+            date.year() - 1
+        };
+        (
+            NaiveDate::from_ymd_opt(year, 8, 1).unwrap(),
+            NaiveDate::from_ymd_opt(year + 1, 7, 31).unwrap(),
+        )
+    };
+    let mut school_years = vec![school_year(from)];
+    while school_years.last().unwrap().1 < to {
+        let next = school_years.last().unwrap().1 + Days::new(1);
+        school_years.push(school_year(next));
+    }
+
+    let mut exams = Vec::new();
+    for exam_type in exam_types {
+        let mut json_exams = Vec::new();
+        for (start, end) in &school_years {
+            let params = JsonExamParams {
+                exam_type_id: exam_type.id,
+                start_date: (*start).into(),
+                end_date: (*end).into(),
+            };
+            if let Ok(result) = session
+                .rpc_cached_with::<Vec<JsonExam>, _>("getExams", params)
+                .await?
+            {
+                json_exams.extend(result);
+            }
+        }
+
+        for json_exam in json_exams {
+            let date = json_exam.date.to_chrono();
+            if date < from || date > to {
+                continue;
+            }
+            let start = merge_naive_date_time_to_datetime(&date, &json_exam.start_time)
+                .map_err(|e| {
+                    Error::DateTime(format!("Failed to convert start time of exam: {:?}", e))
+                })?
+                .to_utc();
+            let end = merge_naive_date_time_to_datetime(&date, &json_exam.end_time)
+                .map_err(|e| {
+                    Error::DateTime(format!("Failed to convert end time of exam: {:?}", e))
+                })?
+                .to_utc();
+
+            exams.push(Exam {
+                exam_type: if exam_type.long_name.is_empty() {
+                    exam_type.name.to_owned()
+                } else {
+                    exam_type.long_name.to_owned()
+                },
+                subjects: names(
+                    &json_exam.subject.into_iter().collect::<Vec<_>>(),
+                    &subjects,
+                    |subject| subject.id,
+                    |subject| subject.name.to_owned(),
+                ),
+                teachers: names(
+                    &json_exam.teachers,
+                    &teachers,
+                    |teacher| teacher.id,
+                    |teacher| teacher.name.to_owned(),
+                ),
+                classes: names(
+                    &json_exam.classes,
+                    &classes,
+                    |class| class.id,
+                    |class| class.name.to_owned(),
+                ),
+                start,
+                end,
+            });
         }
     }
+    exams.sort_by_key(|exam| exam.start);
+
+    Ok(exams)
+}
+
+impl UntisType {
+    /// Resolves the Untis id and element type of this timetable <br>
+    /// Returns None for [UntisType::Own]
+    async fn resolve(&self, session: &UntisSession) -> Result<Option<(usize, ElementType)>, Error> {
+        fn find<T>(
+            items: Vec<T>,
+            name: &str,
+            kind: &str,
+            id: impl Fn(&T) -> usize,
+            names: impl Fn(&T) -> Vec<String>,
+        ) -> Result<usize, Error> {
+            let name = name.trim().to_lowercase();
+            items
+                .iter()
+                .find(|item| names(item).iter().any(|n| n.trim().to_lowercase() == name))
+                .map(id)
+                .ok_or_else(|| {
+                    Error::InvalidInput(format!("no untis {} named '{}' found", kind, name))
+                })
+        }
+
+        let result = match self {
+            UntisType::Own => None,
+            UntisType::OwnClass => Some((session.info().await?.class_id, ElementType::Class)),
+            UntisType::Class(UntisElement::Id(id)) => Some((*id, ElementType::Class)),
+            UntisType::Teacher(UntisElement::Id(id)) => Some((*id, ElementType::Teacher)),
+            UntisType::Room(UntisElement::Id(id)) => Some((*id, ElementType::Room)),
+            UntisType::Subject(UntisElement::Id(id)) => Some((*id, ElementType::Subject)),
+            UntisType::Class(UntisElement::Name(name)) => {
+                let id = find(
+                    session.classes().await?,
+                    name,
+                    "class",
+                    |class| class.id,
+                    |class| vec![class.name.to_owned(), class.long_name.to_owned()],
+                )?;
+                Some((id, ElementType::Class))
+            }
+            UntisType::Teacher(UntisElement::Name(name)) => {
+                let id = find(
+                    session.teachers().await?,
+                    name,
+                    "teacher",
+                    |teacher| teacher.id,
+                    |teacher| {
+                        vec![
+                            teacher.name.to_owned(),
+                            teacher.last_name.to_owned(),
+                            format!("{} {}", teacher.first_name, teacher.last_name),
+                        ]
+                    },
+                )?;
+                Some((id, ElementType::Teacher))
+            }
+            UntisType::Room(UntisElement::Name(name)) => {
+                let id = find(
+                    session.rooms().await?,
+                    name,
+                    "room",
+                    |room| room.id,
+                    |room| vec![room.name.to_owned(), room.long_name.to_owned()],
+                )?;
+                Some((id, ElementType::Room))
+            }
+            UntisType::Subject(UntisElement::Name(name)) => {
+                let id = find(
+                    session.subjects().await?,
+                    name,
+                    "subject",
+                    |subject| subject.id,
+                    |subject| vec![subject.name.to_owned(), subject.long_name.to_owned()],
+                )?;
+                Some((id, ElementType::Subject))
+            }
+        };
+
+        Ok(result)
+    }
+}
+
+/// How lessons are written by [to_ics]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum IcsRecurrence {
+    /// Every lesson becomes a single event
+    None,
+    /// Regular lessons become one weekly repeating event per lesson slot (optionally until the given date) <br>
+    /// Cancelled lessons become cancelled occurrences and changed lessons become single events
+    Weekly(Option<NaiveDate>),
 }
 
+const ICS_TIMEZONE: &str = "Europe/Berlin";
+const ICS_VTIMEZONE: [&str; 16] = [
+    "BEGIN:VTIMEZONE",
+    "TZID:Europe/Berlin",
+    "BEGIN:DAYLIGHT",
+    "TZOFFSETFROM:+0100",
+    "TZOFFSETTO:+0200",
+    "TZNAME:CEST",
+    "DTSTART:19700329T020000",
+    "RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU",
+    "END:DAYLIGHT",
+    "BEGIN:STANDARD",
+    "TZOFFSETFROM:+0200",
+    "TZOFFSETTO:+0100",
+    "TZNAME:CET",
+    "DTSTART:19701025T030000",
+    "RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU",
+    "END:STANDARD",
+];
+
+/// Serializes the lessons of the given weeks to an iCalendar (RFC 5545) file <br>
+/// UIDs only depend on the time and subjects of a lesson, so importing a newer export updates the existing events
+pub fn to_ics(weeks: &[Week], recurrence: &IcsRecurrence) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let entries = weeks
+        .iter()
+        .flat_map(|week| week.entries.iter())
+        .collect::<Vec<_>>();
+
+    let mut events = Vec::new();
+    match recurrence {
+        IcsRecurrence::None => {
+            for entry in entries {
+                events.push(ics_event(entry, &ics_uid(entry, false), &stamp, Vec::new()));
+            }
+        }
+        IcsRecurrence::Weekly(until) => {
+            // Slot UID -> (first occurrence, starts of all occurrences)
+            let mut slots: BTreeMap<String, (&LessonEntry, Vec<DateTime<Utc>>)> = BTreeMap::new();
+            for entry in entries
+                .iter()
+                .filter(|entry| entry.status == LessonEntryStatus::Normal)
+            {
+                let slot = slots
+                    .entry(ics_uid(entry, true))
+                    .or_insert((entry, Vec::new()));
+                if entry.start < slot.0.start {
+                    slot.0 = entry;
+                }
+                slot.1.push(entry.start);
+            }
+
+            let mut singles = Vec::new();
+            for entry in entries
+                .iter()
+                .filter(|entry| entry.status != LessonEntryStatus::Normal)
+            {
+                let uid = ics_uid(entry, true);
+                match slots.get_mut(&uid) {
+                    Some(slot) if entry.status == LessonEntryStatus::Cancelled => {
+                        slot.1.push(entry.start);
+                        singles.push(ics_event(
+                            entry,
+                            &uid,
+                            &stamp,
+                            vec![format!(
+                                "RECURRENCE-ID;TZID={}:{}",
+                                ICS_TIMEZONE,
+                                ics_local_time(&entry.start)
+                            )],
+                        ));
+                    }
+                    _ => singles.push(ics_event(entry, &ics_uid(entry, false), &stamp, Vec::new())),
+                }
+            }
+
+            let mondays = weeks
+                .iter()
+                .map(|week| {
+                    week.week - Days::new(week.week.weekday().num_days_from_monday() as u64)
+                })
+                .collect::<Vec<_>>();
+            for (uid, (first, occurrences)) in slots {
+                let mut rule = String::from("RRULE:FREQ=WEEKLY");
+                if let Some(until) = until {
+                    if let Ok(until) = merge_naive_date_time_to_datetime(
+                        until,
+                        &NaiveTime::from_hms_opt(23, 59, 59).unwrap(),
+                    ) {
+                        rule.push_str(&format!(
+                            ";UNTIL={}",
+                            until.to_utc().format("%Y%m%dT%H%M%SZ")
+                        ));
+                    }
+                }
+                let mut properties = vec![rule];
+
+                // Weeks that were exported but don't contain the lesson (e.g. holidays)
+                let first_start = datetime_to_local(&first.start);
+                for monday in &mondays {
+                    let date =
+                        *monday + Days::new(first_start.weekday().num_days_from_monday() as u64);
+                    let start = merge_naive_date_time_to_datetime(&date, &first_start.time())
+                        .map(|start| start.to_utc());
+                    if let Ok(start) = start {
+                        if start > first.start
+                            && until.is_none_or(|until| date <= until)
+                            && !occurrences.contains(&start)
+                        {
+                            properties.push(format!(
+                                "EXDATE;TZID={}:{}",
+                                ICS_TIMEZONE,
+                                ics_local_time(&start)
+                            ));
+                        }
+                    }
+                }
+                properties.dedup();
+
+                events.push(ics_event(first, &uid, &stamp, properties));
+            }
+            events.extend(singles);
+        }
+    }
+
+    let mut lines = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        String::from("PRODID:-//lanis-rs//Timetable//EN"),
+        String::from("CALSCALE:GREGORIAN"),
+    ];
+    lines.extend(ICS_VTIMEZONE.iter().map(|line| line.to_string()));
+    lines.push(String::from("END:VTIMEZONE"));
+    lines.extend(events.into_iter().flatten());
+    lines.push(String::from("END:VCALENDAR"));
+
+    lines
+        .iter()
+        .map(|line| ics_fold(line))
+        .collect::<Vec<_>>()
+        .join("\r\n")
+        + "\r\n"
+}
+
+/// Builds a stable UID from the time, subjects, teachers, rooms and classes of a lesson <br>
+/// Lessons at the same time (e.g. with [LanisType::All]) get different UIDs as long as one of them differs. <br>
+/// If `weekly` is set the UID only depends on the weekday instead of the date
+fn ics_uid(entry: &LessonEntry, weekly: bool) -> String {
+    let start = datetime_to_local(&entry.start);
+    let time = if weekly {
+        start.format("%a-%H%M").to_string().to_lowercase()
+    } else {
+        start.format("%Y%m%dT%H%M").to_string()
+    };
+    let part = |values: &[String]| {
+        values
+            .join("-")
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect::<String>()
+    };
+    let subjects = part(&entry.subjects);
+    let subjects = if subjects.is_empty() {
+        String::from("lesson")
+    } else {
+        subjects
+    };
+    let mut key = vec![time, subjects];
+    key.extend(
+        [&entry.teachers, &entry.rooms, &entry.classes]
+            .into_iter()
+            .map(|values| part(values))
+            .filter(|part| !part.is_empty()),
+    );
+
+    format!("{}@lanis-rs", key.join("-"))
+}
+
+fn ics_event(entry: &LessonEntry, uid: &str, stamp: &str, properties: Vec<String>) -> Vec<String> {
+    let mut lines = vec![
+        String::from("BEGIN:VEVENT"),
+        format!("UID:{}", uid),
+        format!("DTSTAMP:{}", stamp),
+        format!(
+            "DTSTART;TZID={}:{}",
+            ICS_TIMEZONE,
+            ics_local_time(&entry.start)
+        ),
+        format!("DTEND;TZID={}:{}", ICS_TIMEZONE, ics_local_time(&entry.end)),
+    ];
+    lines.extend(properties);
+    lines.push(format!(
+        "SUMMARY:{}",
+        ics_escape(&entry.subjects.join(", "))
+    ));
+    if !entry.rooms.is_empty() {
+        lines.push(format!("LOCATION:{}", ics_escape(&entry.rooms.join(", "))));
+    }
+
+    let mut description = Vec::new();
+    if !entry.teachers.is_empty() {
+        description.push(entry.teachers.join(", "));
+    }
+    description.extend(entry.lesson_text.iter().cloned());
+    description.extend(entry.substitution_text.iter().cloned());
+    if !description.is_empty() {
+        lines.push(format!(
+            "DESCRIPTION:{}",
+            ics_escape(&description.join("\n"))
+        ));
+    }
+
+    lines.push(String::from(match entry.status {
+        LessonEntryStatus::Cancelled => "STATUS:CANCELLED",
+        _ => "STATUS:CONFIRMED",
+    }));
+    lines.push(String::from("END:VEVENT"));
+
+    lines
+}
+
+fn ics_local_time(date_time: &DateTime<Utc>) -> String {
+    datetime_to_local(date_time)
+        .format("%Y%m%dT%H%M%S")
+        .to_string()
+}
+
+fn ics_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Lines must not be longer than 75 octets, longer lines continue on the next line after a space
+fn ics_fold(line: &str) -> String {
+    let mut result = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            result.push_str("\r\n ");
+            length = 1;
+        }
+        result.push(c);
+        length += c.len_utf8();
+    }
+    result
+}
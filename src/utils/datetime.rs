@@ -1,4 +1,6 @@
-use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime};
+use chrono::{DateTime, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Europe::Berlin;
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
@@ -30,37 +32,63 @@ impl std::fmt::Display for DateTimeError {
 //    Ok(date_time)
 //}
 
-/// Converts +02:00 DateTime to DateTime<FixedOffset> (takes DateTime String in format: %Y-%m-%d %H:%M:%S)
-pub(crate) fn datetime_string_stupid_to_datetime(
-    datetime: &str,
-) -> Result<DateTime<FixedOffset>, DateTimeError> {
-    let date_time =
-        DateTime::parse_from_str(&format!("{} +02:00", datetime), "%Y-%m-%d %H:%M:%S %z").map_err(
-            |e| {
+/// Converts a local [NaiveDateTime] to Europe/Berlin time <br>
+/// Ambiguous times (when the clocks are turned back) resolve to the earlier time and
+/// times that don't exist (when the clocks are turned forward) are shifted forward by the gap
+pub(crate) fn naive_to_local(date_time: &NaiveDateTime) -> Result<DateTime<Tz>, DateTimeError> {
+    match Berlin.from_local_datetime(date_time) {
+        LocalResult::Single(date_time) => Ok(date_time),
+        LocalResult::Ambiguous(earlier, _) => Ok(earlier),
+        LocalResult::None => Berlin
+            .from_local_datetime(&(*date_time + Duration::hours(1)))
+            .earliest()
+            .ok_or_else(|| {
                 DateTimeError::DateTimeInvalid(format!(
-                    "converting '{} +02:00' failed with error '{}'",
-                    datetime, e
+                    "'{}' doesn't exist in Europe/Berlin",
+                    date_time
                 ))
-            },
-        )?;
-    Ok(date_time)
+            }),
+    }
+}
+
+/// Converts a [DateTime<Utc>] to Europe/Berlin time
+pub(crate) fn datetime_to_local(date_time: &DateTime<Utc>) -> DateTime<Tz> {
+    date_time.with_timezone(&Berlin)
 }
 
-/// Converts +02:00 Date & Time to DateTime<FixedOffset> (takes DateTime String in format: %d.%m.%Y %H:%M:%S)
+/// The current time in Europe/Berlin
+pub(crate) fn now_local() -> DateTime<Tz> {
+    datetime_to_local(&Utc::now())
+}
+
+/// Converts Europe/Berlin DateTime to DateTime<Tz> (takes DateTime String in format: %Y-%m-%d %H:%M:%S)
+pub(crate) fn datetime_string_stupid_to_datetime(
+    datetime: &str,
+) -> Result<DateTime<Tz>, DateTimeError> {
+    let date_time = NaiveDateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M:%S").map_err(|e| {
+        DateTimeError::DateTimeInvalid(format!(
+            "converting '{}' failed with error '{}'",
+            datetime, e
+        ))
+    })?;
+    naive_to_local(&date_time)
+}
+
+/// Converts Europe/Berlin Date & Time to DateTime<Tz> (takes DateTime String in format: %d.%m.%Y %H:%M:%S)
 pub(crate) fn date_time_string_to_datetime(
     date: &str,
     time: &str,
-) -> Result<DateTime<FixedOffset>, DateTimeError> {
+) -> Result<DateTime<Tz>, DateTimeError> {
     let date_time =
-        DateTime::parse_from_str(&format!("{} {} +02:00", date, time), "%d.%m.%Y %H:%M:%S %z")
-            .map_err(|e| {
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%d.%m.%Y %H:%M:%S").map_err(
+            |e| {
                 DateTimeError::DateTimeInvalid(format!(
-                    "converting '{} {}' +02:00 failed with error '{}'",
+                    "converting '{} {}' failed with error '{}'",
                     date, time, e
                 ))
-            })
-            .unwrap();
-    Ok(date_time)
+            },
+        )?;
+    naive_to_local(&date_time)
 }
 
 /// Converts Date to NaiveDate (%d.%m.%Y)
@@ -72,18 +100,10 @@ pub(crate) fn date_string_to_naivedate(date: &str) -> Result<NaiveDate, DateTime
     Ok(date)
 }
 
-/// Merges a [NaiveDate] with a [NaiveTime] to a [DateTime] (CEST)
+/// Merges a [NaiveDate] with a [NaiveTime] to a [DateTime] (Europe/Berlin)
 pub(crate) fn merge_naive_date_time_to_datetime(
     date: &NaiveDate,
     time: &NaiveTime,
-) -> Result<DateTime<FixedOffset>, DateTimeError> {
-    let date_time =
-        DateTime::parse_from_str(&format!("{} {} +02:00", date, time), "%Y-%m-%d %H:%M:%S %z")
-            .map_err(|e| {
-                DateTimeError::DateTimeInvalid(format!(
-                    "merging '{} {}' +02:00 failed with error '{}'",
-                    date, time, e
-                ))
-            })?;
-    Ok(date_time)
+) -> Result<DateTime<Tz>, DateTimeError> {
+    naive_to_local(&date.and_time(*time))
 }
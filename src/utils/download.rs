@@ -0,0 +1,197 @@
+use crate::Error;
+use reqwest::Client;
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::path::Path;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// The state of a running download
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct DownloadProgress {
+    /// Bytes that were written so far
+    pub downloaded: u64,
+    /// The size of the file (None if the server didn't send a Content-Length)
+    pub total: Option<u64>,
+}
+
+/// Streams the file at `url` into `writer` and calls `progress` after every chunk <br>
+/// Returns the amount of written bytes
+pub(crate) async fn download_to_writer<W, F>(
+    url: &str,
+    client: &Client,
+    writer: &mut W,
+    mut progress: F,
+) -> Result<u64, Error>
+where
+    W: AsyncWrite + Unpin + ?Sized,
+    F: FnMut(DownloadProgress),
+{
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| Error::Network(format!("failed to get '{}' with error '{}'", url, e)))?;
+
+    let total = response.content_length();
+    let mut downloaded = 0;
+    progress(DownloadProgress { downloaded, total });
+
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| Error::Network(format!("failed to download '{}' with error '{}'", url, e)))?
+    {
+        writer
+            .write_all(&chunk)
+            .await
+            .map_err(|e| Error::FileSystem(format!("failed to write file with error '{}'", e)))?;
+        downloaded += chunk.len() as u64;
+        progress(DownloadProgress { downloaded, total });
+    }
+
+    writer
+        .flush()
+        .await
+        .map_err(|e| Error::FileSystem(format!("failed to write file with error '{}'", e)))?;
+
+    Ok(downloaded)
+}
+
+/// Streams the file at `url` to `path` (see [download_to_writer]) <br>
+/// Missing parent folders are created. The file is written next to `path` first and only replaces an existing file once the download is complete,
+/// so a failed download never destroys a file that was already there
+pub(crate) async fn download_to_path<F>(
+    url: &str,
+    client: &Client,
+    path: &Path,
+    progress: F,
+) -> Result<u64, Error>
+where
+    F: FnMut(DownloadProgress),
+{
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|e| {
+            Error::FileSystem(format!(
+                "failed to create folder '{}' with error '{}'",
+                parent.display(),
+                e
+            ))
+        })?;
+    }
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or(Error::InvalidInput(format!(
+            "'{}' is not a file",
+            path.display()
+        )))?;
+    let temporary = path.with_file_name(format!("{}.part", file_name));
+
+    let mut file = tokio::fs::File::create(&temporary).await.map_err(|e| {
+        Error::FileSystem(format!(
+            "failed to create file at '{}' with error '{}'",
+            temporary.display(),
+            e
+        ))
+    })?;
+
+    let size = match download_to_writer(url, client, &mut file, progress).await {
+        Ok(size) => size,
+        Err(error) => {
+            drop(file);
+            let _ = tokio::fs::remove_file(&temporary).await;
+            return Err(error);
+        }
+    };
+    drop(file);
+
+    tokio::fs::rename(&temporary, path).await.map_err(|e| {
+        Error::FileSystem(format!(
+            "failed to move file to '{}' with error '{}'",
+            path.display(),
+            e
+        ))
+    })?;
+
+    Ok(size)
+}
+
+/// A file from Lanis that can be downloaded (e.g. an attachment of a lesson entry or an uploaded file)
+pub trait RemoteFile {
+    /// The full url of the file
+    fn url(&self) -> &str;
+
+    /// The size in bytes as shown by Lanis (None if Lanis doesn't show it) <br>
+    /// Lanis rounds the size (e.g. "1,2 MB"), so it can differ from the size of the downloaded file
+    fn size(&self) -> Option<u64> {
+        None
+    }
+
+    /// Streams the file into `writer` and calls `progress` after every received chunk <br>
+    /// Returns the amount of written bytes
+    fn download_to<W, F>(
+        &self,
+        writer: &mut W,
+        client: &Client,
+        progress: F,
+    ) -> impl Future<Output = Result<u64, Error>>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+        F: FnMut(DownloadProgress),
+    {
+        download_to_writer(self.url(), client, writer, progress)
+    }
+
+    /// Downloads the file to `path` and calls `progress` after every received chunk <br>
+    /// An existing file at `path` is only replaced if the download succeeds
+    fn download<F>(
+        &self,
+        path: &Path,
+        client: &Client,
+        progress: F,
+    ) -> impl Future<Output = Result<u64, Error>>
+    where
+        F: FnMut(DownloadProgress),
+    {
+        download_to_path(self.url(), client, path, progress)
+    }
+}
+
+/// Replaces characters that aren't allowed in file or folder names
+pub(crate) fn sanitize_file_name(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>();
+    let name = name.trim().trim_matches('.').trim();
+
+    if name.is_empty() {
+        String::from("_")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Appends a number to `name` (before the extension) if it is already in `used` and adds the result to `used` <br>
+/// The names are compared case-insensitively, because some file systems don't distinguish them
+pub(crate) fn unique_file_name(name: &str, used: &mut BTreeSet<String>) -> String {
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (name, String::new()),
+    };
+
+    let mut unique = name.to_string();
+    let mut number = 1;
+    while !used.insert(unique.to_lowercase()) {
+        number += 1;
+        unique = format!("{} ({}){}", stem, number, extension);
+    }
+
+    unique
+}
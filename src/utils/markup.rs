@@ -0,0 +1,710 @@
+use regex::Regex;
+use scraper::{ElementRef, Html, Node};
+use serde::{Deserialize, Serialize};
+
+/// Inline content of a [Block]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum Inline {
+    Text(String),
+    /// `**text**`
+    Bold(Vec<Inline>),
+    /// `--text--`
+    Italic(Vec<Inline>),
+    /// `__text__`
+    Underline(Vec<Inline>),
+    /// `~~text~~`
+    Strikethrough(Vec<Inline>),
+    /// `_(text)`
+    Subscript(Vec<Inline>),
+    /// `^(text)`
+    Superscript(Vec<Inline>),
+    /// `` `text` ``
+    Code(String),
+    /// A link (Lanis detects links automatically, so they can only come from HTML or plain URLs)
+    Link {
+        url: String,
+        content: Vec<Inline>,
+    },
+    LineBreak,
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum Block {
+    Paragraph(Vec<Inline>),
+    /// Every item is a list of [Inline]s
+    List {
+        ordered: bool,
+        items: Vec<Vec<Inline>>,
+    },
+    CodeBlock(String),
+}
+
+/// Text with Lanis formatting as a tree <br>
+/// See [here](https://support.schulportal.hessen.de/knowledgebase.php?article=664) for the syntax of Lanis
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Serialize, Deserialize)]
+pub struct RichText {
+    pub blocks: Vec<Block>,
+}
+
+/// The paired delimiters of Lanis formatting
+const DELIMITERS: [(&str, &str); 6] = [
+    ("**", "**"),
+    ("__", "__"),
+    ("--", "--"),
+    ("~~", "~~"),
+    ("_(", ")"),
+    ("^(", ")"),
+];
+
+impl RichText {
+    /// Parses Lanis formatting (e.g. `**bold**`) and leftover HTML (e.g. `<br>`, `<ul>` or `<a>`) <br>
+    /// Unclosed formatting is kept as text
+    pub fn parse(text: &str) -> Self {
+        let mut builder = BlockBuilder::default();
+
+        let html_re = Regex::new(r"(?i)</?\s*(b|strong|i|em|u|s|strike|del|code|pre|a|br|ul|ol|li|p|div|sub|sup|span|font)\b[^>]*>").unwrap();
+        if html_re.is_match(text) {
+            // The line breaks of HTML are the tags, newlines after them are only formatting
+            let break_re = Regex::new(r"(?i)(<br\s*/?>)\r?\n").unwrap();
+            let fragment = Html::parse_fragment(&break_re.replace_all(text, "$1"));
+            builder.push_children(fragment.root_element());
+        } else {
+            builder.push_text(text);
+        }
+        builder.flush();
+
+        Self {
+            blocks: builder.blocks,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Renders the text without any formatting <br>
+    /// Links are shown as "text (url)" if the text isn't the url itself
+    pub fn to_plain_text(&self) -> String {
+        self.render(&Renderer::PlainText)
+    }
+
+    /// Renders the text as HTML (all text is escaped)
+    pub fn to_html(&self) -> String {
+        self.blocks
+            .iter()
+            .map(|block| match block {
+                Block::Paragraph(inlines) => format!("<p>{}</p>", inlines_to_html(inlines)),
+                Block::List { ordered, items } => {
+                    let tag = if *ordered { "ol" } else { "ul" };
+                    let items = items
+                        .iter()
+                        .map(|item| format!("<li>{}</li>", inlines_to_html(item)))
+                        .collect::<String>();
+                    format!("<{tag}>{items}</{tag}>")
+                }
+                Block::CodeBlock(code) => format!("<pre><code>{}</code></pre>", escape_html(code)),
+            })
+            .collect()
+    }
+
+    /// Renders the text as Markdown (underline, sub- and superscript use inline HTML) <br>
+    /// Characters of the text that Markdown would interpret as formatting are escaped
+    pub fn to_markdown(&self) -> String {
+        self.render(&Renderer::Markdown)
+    }
+
+    /// Renders the text with Lanis formatting (e.g. for [crate::modules::messages::Conversation::reply])
+    pub fn to_lanis_markup(&self) -> String {
+        self.render(&Renderer::Lanis)
+    }
+
+    fn render(&self, renderer: &Renderer) -> String {
+        self.blocks
+            .iter()
+            .map(|block| match block {
+                Block::Paragraph(inlines) => renderer.inlines(inlines),
+                Block::List { ordered, items } => items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| {
+                        let bullet = if *ordered {
+                            format!("{}.", index + 1)
+                        } else {
+                            String::from("-")
+                        };
+                        format!("{} {}", bullet, renderer.inlines(item))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                Block::CodeBlock(code) => match renderer {
+                    Renderer::PlainText => code.to_owned(),
+                    Renderer::Markdown => format!("```\n{}\n```", code),
+                    Renderer::Lanis => code
+                        .lines()
+                        .map(|line| format!("`{}`", line))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                },
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+enum Renderer {
+    PlainText,
+    Markdown,
+    Lanis,
+}
+
+impl Renderer {
+    fn inlines(&self, inlines: &[Inline]) -> String {
+        let mut line_start = true;
+        inlines
+            .iter()
+            .map(|inline| {
+                let rendered = match (self, inline) {
+                    (Renderer::Markdown, Inline::Text(text)) => escape_markdown(text, line_start),
+                    _ => self.inline(inline),
+                };
+                line_start = matches!(inline, Inline::LineBreak);
+                rendered
+            })
+            .collect()
+    }
+
+    fn inline(&self, inline: &Inline) -> String {
+        let wrap = |open: &str, inlines: &[Inline], close: &str| {
+            format!("{}{}{}", open, self.inlines(inlines), close)
+        };
+
+        match (self, inline) {
+            (Renderer::Markdown, Inline::Text(text)) => escape_markdown(text, false),
+            (_, Inline::Text(text)) => text.to_owned(),
+            // Markdown needs two trailing spaces for a line break inside a paragraph
+            (Renderer::Markdown, Inline::LineBreak) => String::from("  \n"),
+            (_, Inline::LineBreak) => String::from("\n"),
+            (Renderer::PlainText, Inline::Code(code)) => code.to_owned(),
+            (Renderer::PlainText, Inline::Link { url, content }) => {
+                let text = self.inlines(content);
+                if text.is_empty() || text == *url {
+                    url.to_owned()
+                } else {
+                    format!("{} ({})", text, url)
+                }
+            }
+            (Renderer::PlainText, Inline::Bold(inlines))
+            | (Renderer::PlainText, Inline::Italic(inlines))
+            | (Renderer::PlainText, Inline::Underline(inlines))
+            | (Renderer::PlainText, Inline::Strikethrough(inlines))
+            | (Renderer::PlainText, Inline::Subscript(inlines))
+            | (Renderer::PlainText, Inline::Superscript(inlines)) => self.inlines(inlines),
+
+            (Renderer::Markdown, Inline::Bold(inlines)) => wrap("**", inlines, "**"),
+            (Renderer::Markdown, Inline::Italic(inlines)) => wrap("*", inlines, "*"),
+            (Renderer::Markdown, Inline::Underline(inlines)) => wrap("<u>", inlines, "</u>"),
+            (Renderer::Markdown, Inline::Strikethrough(inlines)) => wrap("~~", inlines, "~~"),
+            (Renderer::Markdown, Inline::Subscript(inlines)) => wrap("<sub>", inlines, "</sub>"),
+            (Renderer::Markdown, Inline::Superscript(inlines)) => wrap("<sup>", inlines, "</sup>"),
+            (Renderer::Markdown, Inline::Code(code)) => format!("`{}`", code),
+            (Renderer::Markdown, Inline::Link { url, content }) => {
+                format!("[{}]({})", self.inlines(content), url)
+            }
+
+            (Renderer::Lanis, Inline::Bold(inlines)) => wrap("**", inlines, "**"),
+            (Renderer::Lanis, Inline::Italic(inlines)) => wrap("--", inlines, "--"),
+            (Renderer::Lanis, Inline::Underline(inlines)) => wrap("__", inlines, "__"),
+            (Renderer::Lanis, Inline::Strikethrough(inlines)) => wrap("~~", inlines, "~~"),
+            (Renderer::Lanis, Inline::Subscript(inlines)) => wrap("_(", inlines, ")"),
+            (Renderer::Lanis, Inline::Superscript(inlines)) => wrap("^(", inlines, ")"),
+            (Renderer::Lanis, Inline::Code(code)) => format!("`{}`", code),
+            // Lanis detects links by itself
+            (Renderer::Lanis, Inline::Link { url, content }) => {
+                Renderer::PlainText.inline(&Inline::Link {
+                    url: url.to_owned(),
+                    content: content.to_owned(),
+                })
+            }
+        }
+    }
+}
+
+fn inlines_to_html(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|inline| {
+            let wrap = |tag: &str, inlines: &[Inline]| {
+                format!("<{tag}>{}</{tag}>", inlines_to_html(inlines))
+            };
+            match inline {
+                Inline::Text(text) => escape_html(text),
+                Inline::Bold(inlines) => wrap("b", inlines),
+                Inline::Italic(inlines) => wrap("i", inlines),
+                Inline::Underline(inlines) => wrap("u", inlines),
+                Inline::Strikethrough(inlines) => wrap("s", inlines),
+                Inline::Subscript(inlines) => wrap("sub", inlines),
+                Inline::Superscript(inlines) => wrap("sup", inlines),
+                Inline::Code(code) => format!("<code>{}</code>", escape_html(code)),
+                Inline::Link { url, content } => format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape_html(url),
+                    inlines_to_html(content)
+                ),
+                Inline::LineBreak => String::from("<br>"),
+            }
+        })
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Escapes characters that Markdown would interpret as formatting <br>
+/// Headings, quotes, list bullets and numbers are only escaped at the start of a line (`line_start` is true if `text` starts a line)
+fn escape_markdown(text: &str, mut line_start: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(character) = chars.next() {
+        match character {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '~' | '|' => escaped.push('\\'),
+            '#' | '>' | '-' | '+' | '=' if line_start => escaped.push('\\'),
+            // Ordered list items (e.g. "1. " or "2) ")
+            '0'..='9' if line_start => {
+                escaped.push(character);
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    escaped.push(digit);
+                }
+                if let Some(delimiter) = chars.next_if(|next| matches!(next, '.' | ')')) {
+                    escaped.push('\\');
+                    escaped.push(delimiter);
+                }
+                line_start = false;
+                continue;
+            }
+            _ => {}
+        }
+        escaped.push(character);
+        line_start = character == '\n' || (line_start && character == ' ');
+    }
+
+    escaped
+}
+
+/// Collects [Block]s while walking through text and HTML
+#[derive(Default)]
+struct BlockBuilder {
+    blocks: Vec<Block>,
+    inlines: Vec<Inline>,
+    /// True if the last line was a list item (so the next list item continues the list)
+    in_list: bool,
+}
+
+impl BlockBuilder {
+    /// Finishes the current paragraph
+    fn flush(&mut self) {
+        while self.inlines.last() == Some(&Inline::LineBreak) {
+            self.inlines.pop();
+        }
+        if !self.inlines.is_empty() {
+            self.blocks
+                .push(Block::Paragraph(std::mem::take(&mut self.inlines)));
+        }
+        self.in_list = false;
+    }
+
+    fn push_list_item(&mut self, ordered: bool, item: Vec<Inline>) {
+        match self.blocks.last_mut() {
+            Some(Block::List {
+                ordered: list_ordered,
+                items,
+            }) if self.in_list && *list_ordered == ordered => items.push(item),
+            _ => {
+                self.flush();
+                self.blocks.push(Block::List {
+                    ordered,
+                    items: vec![item],
+                });
+            }
+        }
+        self.in_list = true;
+    }
+
+    /// Adds text with Lanis formatting (lines starting with "- ", "* ", "• " or "1. " are list items, empty lines start a new paragraph)
+    fn push_text(&mut self, text: &str) {
+        let list_re = Regex::new(r"^\s*(?:([-*•])|(\d+)[.)])\s+(.*)$").unwrap();
+
+        for (index, line) in text.split('\n').enumerate() {
+            let line = line.trim_end_matches('\r');
+            if index > 0 && line.trim().is_empty() {
+                self.flush();
+            } else if let Some(captures) = list_re.captures(line) {
+                if !self.inlines.is_empty() {
+                    self.flush();
+                }
+                let ordered = captures.get(2).is_some();
+                self.push_list_item(ordered, parse_inline(captures[3].trim_end()));
+            } else if !line.is_empty() {
+                if self.in_list {
+                    self.flush();
+                }
+                if index > 0 && !self.inlines.is_empty() {
+                    self.inlines.push(Inline::LineBreak);
+                }
+                self.inlines.extend(parse_inline(line));
+            }
+        }
+    }
+
+    fn push_line_break(&mut self) {
+        if self.in_list {
+            self.flush();
+        } else if !self.inlines.is_empty() {
+            self.inlines.push(Inline::LineBreak);
+        }
+    }
+
+    fn push_children(&mut self, element: ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.push_text(text),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        self.push_element(child);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn push_element(&mut self, element: ElementRef) {
+        match element.value().name() {
+            "br" => self.push_line_break(),
+            "p" | "div" => {
+                self.flush();
+                self.push_children(element);
+                self.flush();
+            }
+            "ul" | "ol" => {
+                self.flush();
+                let ordered = element.value().name() == "ol";
+                for item in element.child_elements() {
+                    self.push_list_item(ordered, trim_inlines(inlines_of(item)));
+                }
+                self.flush();
+            }
+            "pre" => {
+                self.flush();
+                self.blocks.push(Block::CodeBlock(
+                    element
+                        .text()
+                        .collect::<String>()
+                        .trim_matches('\n')
+                        .to_string(),
+                ));
+            }
+            name if is_inline_element(name) => {
+                if self.in_list {
+                    self.flush();
+                }
+                self.inlines.push(inline_element(element));
+            }
+            _ => self.push_children(element),
+        }
+    }
+}
+
+fn is_inline_element(name: &str) -> bool {
+    matches!(
+        name,
+        "b" | "strong" | "i" | "em" | "u" | "s" | "strike" | "del" | "sub" | "sup" | "code" | "a"
+    )
+}
+
+fn inline_element(element: ElementRef) -> Inline {
+    let content = inlines_of(element);
+    match element.value().name() {
+        "b" | "strong" => Inline::Bold(content),
+        "i" | "em" => Inline::Italic(content),
+        "u" => Inline::Underline(content),
+        "s" | "strike" | "del" => Inline::Strikethrough(content),
+        "sub" => Inline::Subscript(content),
+        "sup" => Inline::Superscript(content),
+        "code" => Inline::Code(element.text().collect()),
+        _ => {
+            let url = element.value().attr("href").unwrap_or_default().to_string();
+            let content = match content.as_slice() {
+                // A link that only contains its own url was detected by the parser already
+                [Inline::Link { content, .. }] => content.to_owned(),
+                _ => content,
+            };
+            Inline::Link { url, content }
+        }
+    }
+}
+
+/// The inline content of an element (block elements inside are flattened)
+fn inlines_of(element: ElementRef) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => {
+                for (index, line) in text.split('\n').enumerate() {
+                    if index > 0 {
+                        inlines.push(Inline::LineBreak);
+                    }
+                    inlines.extend(parse_inline(line));
+                }
+            }
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    match child.value().name() {
+                        "br" => inlines.push(Inline::LineBreak),
+                        name if is_inline_element(name) => inlines.push(inline_element(child)),
+                        _ => inlines.extend(inlines_of(child)),
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    inlines
+}
+
+/// Removes line breaks and whitespace at the start and the end
+fn trim_inlines(mut inlines: Vec<Inline>) -> Vec<Inline> {
+    while inlines.first() == Some(&Inline::LineBreak) {
+        inlines.remove(0);
+    }
+    while inlines.last() == Some(&Inline::LineBreak) {
+        inlines.pop();
+    }
+    if let Some(Inline::Text(text)) = inlines.first_mut() {
+        *text = text.trim_start().to_string();
+    }
+    if let Some(Inline::Text(text)) = inlines.last_mut() {
+        *text = text.trim_end().to_string();
+    }
+    inlines.retain(|inline| *inline != Inline::Text(String::new()));
+
+    inlines
+}
+
+/// Parses the inline formatting of a single line
+pub(crate) fn parse_inline(text: &str) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    'outer: while let Some(c) = rest.chars().next() {
+        if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                if end > 0 {
+                    push_plain(&mut inlines, &mut plain);
+                    inlines.push(Inline::Code(rest[1..end + 1].to_string()));
+                    rest = &rest[end + 2..];
+                    continue;
+                }
+            }
+        }
+
+        for (open, close) in DELIMITERS {
+            let Some(after_open) = rest.strip_prefix(open) else {
+                continue;
+            };
+            // Formatting has to start with a non-whitespace character (e.g. "a -- b" isn't italic)
+            if after_open.starts_with(char::is_whitespace) || after_open.is_empty() {
+                continue;
+            }
+            if let Some(end) = find_closing(after_open, open, close) {
+                push_plain(&mut inlines, &mut plain);
+                let content = parse_inline(&after_open[..end]);
+                inlines.push(match open {
+                    "**" => Inline::Bold(content),
+                    "__" => Inline::Underline(content),
+                    "--" => Inline::Italic(content),
+                    "~~" => Inline::Strikethrough(content),
+                    "_(" => Inline::Subscript(content),
+                    _ => Inline::Superscript(content),
+                });
+                rest = &after_open[end + close.len()..];
+                continue 'outer;
+            }
+        }
+
+        if rest.starts_with("http://") || rest.starts_with("https://") {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let url = rest[..end].trim_end_matches(['.', ',', ';', ':', '!', '?', ')']);
+            push_plain(&mut inlines, &mut plain);
+            inlines.push(Inline::Link {
+                url: url.to_string(),
+                content: vec![Inline::Text(url.to_string())],
+            });
+            rest = &rest[url.len()..];
+            continue;
+        }
+
+        plain.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    push_plain(&mut inlines, &mut plain);
+
+    inlines
+}
+
+/// Finds the end of formatting that started with `open` (nested brackets are skipped for `_(` and `^(`)
+fn find_closing(text: &str, open: &str, close: &str) -> Option<usize> {
+    if close == ")" {
+        let mut depth = 0;
+        for (index, c) in text.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' if depth == 0 => return (index > 0).then_some(index),
+                ')' => depth -= 1,
+                _ => (),
+            }
+        }
+        return None;
+    }
+
+    let mut start = 0;
+    while let Some(found) = text[start..].find(close) {
+        let index = start + found;
+        // The closing delimiter has to follow a non-whitespace character
+        if index > 0 && !text[..index].ends_with(char::is_whitespace) {
+            return Some(index);
+        }
+        start = index + open.len();
+    }
+
+    None
+}
+
+fn push_plain(inlines: &mut Vec<Inline>, plain: &mut String) {
+    if !plain.is_empty() {
+        inlines.push(Inline::Text(std::mem::take(plain)));
+    }
+}
+
+/// Builds text with Lanis formatting (e.g. for [crate::modules::messages::Conversation::reply] or [crate::modules::messages::create_conversation]) <br>
+/// NOTE: Lanis has no way to escape formatting, so text that contains e.g. `**` may still be formatted by Lanis
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct LanisMarkupBuilder {
+    text: RichText,
+    inlines: Vec<Inline>,
+}
+
+impl LanisMarkupBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(self, text: &str) -> Self {
+        let mut inlines = Vec::new();
+        for (index, line) in text.split('\n').enumerate() {
+            if index > 0 {
+                inlines.push(Inline::LineBreak);
+            }
+            if !line.is_empty() {
+                inlines.push(Inline::Text(line.to_string()));
+            }
+        }
+        self.push(inlines)
+    }
+
+    pub fn bold(self, text: &str) -> Self {
+        self.push(vec![Inline::Bold(vec![Inline::Text(text.to_string())])])
+    }
+
+    pub fn italic(self, text: &str) -> Self {
+        self.push(vec![Inline::Italic(vec![Inline::Text(text.to_string())])])
+    }
+
+    pub fn underline(self, text: &str) -> Self {
+        self.push(vec![Inline::Underline(vec![Inline::Text(
+            text.to_string(),
+        )])])
+    }
+
+    pub fn strikethrough(self, text: &str) -> Self {
+        self.push(vec![Inline::Strikethrough(vec![Inline::Text(
+            text.to_string(),
+        )])])
+    }
+
+    pub fn subscript(self, text: &str) -> Self {
+        self.push(vec![Inline::Subscript(vec![Inline::Text(
+            text.to_string(),
+        )])])
+    }
+
+    pub fn superscript(self, text: &str) -> Self {
+        self.push(vec![Inline::Superscript(vec![Inline::Text(
+            text.to_string(),
+        )])])
+    }
+
+    pub fn code(self, code: &str) -> Self {
+        self.push(vec![Inline::Code(code.to_string())])
+    }
+
+    pub fn link(self, text: &str, url: &str) -> Self {
+        self.push(vec![Inline::Link {
+            url: url.to_string(),
+            content: vec![Inline::Text(text.to_string())],
+        }])
+    }
+
+    pub fn line_break(self) -> Self {
+        self.push(vec![Inline::LineBreak])
+    }
+
+    /// Starts a new paragraph
+    pub fn paragraph(mut self) -> Self {
+        self.finish_paragraph();
+        self
+    }
+
+    /// Adds a list with the given items
+    pub fn list(mut self, ordered: bool, items: &[&str]) -> Self {
+        self.finish_paragraph();
+        self.text.blocks.push(Block::List {
+            ordered,
+            items: items
+                .iter()
+                .map(|item| vec![Inline::Text(item.to_string())])
+                .collect(),
+        });
+        self
+    }
+
+    /// Adds any [Inline]s (e.g. nested formatting)
+    pub fn push(mut self, inlines: Vec<Inline>) -> Self {
+        self.inlines.extend(inlines);
+        self
+    }
+
+    fn finish_paragraph(&mut self) {
+        if !self.inlines.is_empty() {
+            self.text
+                .blocks
+                .push(Block::Paragraph(std::mem::take(&mut self.inlines)));
+        }
+    }
+
+    pub fn build_rich_text(mut self) -> RichText {
+        self.finish_paragraph();
+        self.text
+    }
+
+    /// Returns the text with Lanis formatting
+    pub fn build(self) -> String {
+        self.build_rich_text().to_lanis_markup()
+    }
+}
@@ -2,3 +2,5 @@ pub(crate) mod constants;
 pub(crate) mod conversion;
 pub mod crypt;
 pub(crate) mod datetime;
+pub mod download;
+pub mod markup;